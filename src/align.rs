@@ -0,0 +1,82 @@
+//! Positioning shapes relative to the origin and to each other using their bounds.
+
+use crate::*;
+
+impl BBox3 {
+    /// Point of the box picked out by `aims`. No aims is the center, `[Aim::U]` the center of the top face
+    /// and `[Aim::U, Aim::N, Aim::E]` the top north east corner.
+    pub fn anchor(&self, aims: &[Aim]) -> XYZ {
+        aims.iter().fold(self.center(), |p, aim| {
+            let XYZ(x, y, z) = aim.xyz();
+            let pick = |d: f32, c: f32, lo: f32, hi: f32| if d > 0. { hi } else if d < 0. { lo } else { c };
+            XYZ(pick(x, p.0, self.min.0, self.max.0),
+                pick(y, p.1, self.min.1, self.max.1),
+                pick(z, p.2, self.min.2, self.max.2))
+        })
+    }
+}
+
+impl D3 {
+    /// Point of the bounding box picked out by `aims`, see `BBox3::anchor`.
+    pub fn anchor(&self, aims: &[Aim]) -> XYZ {
+        self.bounds().anchor(aims)
+    }
+
+    /// Translate so the anchor picked out by `aims` lands on `xyz`.
+    pub fn place_at(self, aims: &[Aim], xyz: XYZ) -> D3 {
+        let shift = xyz - self.anchor(aims);
+        self.translate(shift)
+    }
+
+    /// Translate so the center of the bounding box is at the origin.
+    pub fn center(self) -> D3 {
+        self.place_at(&[], v3(0, 0, 0))
+    }
+
+    /// Translate along the axis of `aim` so the shape lies on the `aim` side of the origin touching it.
+    /// `align(Aim::U)` puts the shape on the XY plane.
+    pub fn align(self, aim: Aim) -> D3 {
+        let XYZ(x, y, z) = aim.xyz();
+        let XYZ(ax, ay, az) = self.anchor(&[aim.opposite()]);
+        let shift = |d: f32, a: f32| if d == 0. { 0. } else { -a };
+        self.translate(v3(shift(x, ax), shift(y, ay), shift(z, az)))
+    }
+
+    /// Translate onto the `aim` face of `other`, centered on that face.
+    /// `place_on(&base, Aim::U)` stacks `self` on top of `base`.
+    pub fn place_on(self, other: &D3, aim: Aim) -> D3 {
+        self.place_at(&[aim.opposite()], other.anchor(&[aim]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_anchor() {
+        let c = D3::cuboid(v3(2, 4, 6));
+        assert_eq!(c.anchor(&[]), v3(1, 2, 3));
+        assert_eq!(c.anchor(&[Aim::U]), v3(1, 2, 6));
+        assert_eq!(c.anchor(&[Aim::D, Aim::S, Aim::W]), v3(0, 0, 0));
+    }
+
+    #[test]
+    fn test_center() {
+        assert_eq!(D3::cube(2).center().scad(),
+            "translate(v = [-1, -1, -1]) {\n  cube(size = 2);\n}");
+    }
+
+    #[test]
+    fn test_align() {
+        assert_eq!(D3::sphere(3).align(Aim::U).scad(),
+            "translate(v = [0, 0, 3]) {\n  sphere(r = 3);\n}");
+        assert_eq!(D3::cube(2).align(Aim::W).bounds().max, v3(0, 2, 2));
+    }
+
+    #[test]
+    fn test_place_on() {
+        let base = D3::cuboid(v3(10, 10, 2));
+        assert_eq!(D3::cube(2).place_on(&base, Aim::U).bounds(), BBox3::new(v3(4, 4, 2), v3(6, 6, 4)));
+    }
+}
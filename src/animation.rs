@@ -0,0 +1,114 @@
+//! Animation over OpenSCAD's `$t`, either keyframed in the SCAD or sampled into frames.
+
+use crate::*;
+use std::path::{Path, PathBuf};
+
+/// Piecewise linear motion over the animation time `$t` from 0 to 1, given as `(t, value)` keys.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Keyframes {
+    Translate(Vec<(f32, XYZ)>),
+    Rotate(Vec<(f32, XYZ)>),
+}
+
+impl Keyframes {
+    pub fn keys(&self) -> &Vec<(f32, XYZ)> {
+        match self {
+            Keyframes::Translate(keys) | Keyframes::Rotate(keys) => keys,
+        }
+    }
+
+    /// Value at time `t`, holding the first and last values outside the keys like OpenSCAD's `lookup`.
+    pub fn at(&self, t: f32) -> XYZ {
+        let keys = self.keys();
+        match keys.iter().position(|(tk, _)| *tk > t) {
+            None => keys.last().map(|k| k.1).unwrap_or(v3(0, 0, 0)),
+            Some(0) => keys[0].1,
+            Some(ii) => {
+                let ((t0, a), (t1, b)) = (keys[ii - 1], keys[ii]);
+                let s = (t - t0) / (t1 - t0);
+                a + v3((b.0 - a.0) * s, (b.1 - a.1) * s, (b.2 - a.2) * s)
+            },
+        }
+    }
+
+    /// SCAD vector of `lookup($t, ...)` expressions for the value at `$t`.
+    pub fn expression(&self) -> String {
+        let lookup = |f: fn(&XYZ) -> f32| format!("lookup($t, [{}])",
+            self.keys().iter().map(|(t, xyz)| format!("[{}, {}]", t, f(xyz))).collect::<Vec<_>>().join(", "));
+        format!("[{}, {}, {}]", lookup(|xyz| xyz.0), lookup(|xyz| xyz.1), lookup(|xyz| xyz.2))
+    }
+}
+
+impl D3 {
+    /// Translate along the keyframed path as `$t` runs from 0 to 1.
+    pub fn animate_translate(self, keys: Vec<(f32, XYZ)>) -> D3 {
+        D3::Animate(Keyframes::Translate(keys), Box::new(self))
+    }
+
+    /// Rotate through the keyframed angles as `$t` runs from 0 to 1.
+    pub fn animate_rotate(self, keys: Vec<(f32, XYZ)>) -> D3 {
+        D3::Animate(Keyframes::Rotate(keys), Box::new(self))
+    }
+}
+
+/// An animation of `steps` frames where frame `i` is `f(i / steps)`.
+pub struct Animation<F: Fn(f32) -> D3> {
+    pub steps: u32,
+    pub f: F,
+}
+
+impl<F: Fn(f32) -> D3> Animation<F> {
+    pub fn new(steps: u32, f: F) -> Animation<F> {
+        Animation { steps, f }
+    }
+
+    pub fn frame(&self, i: u32) -> D3 {
+        (self.f)(i as f32 / self.steps as f32)
+    }
+
+    pub fn frames(&self) -> Vec<D3> {
+        (0..self.steps).map(|ii| self.frame(ii)).collect()
+    }
+
+    /// A single SCAD file showing the frame picked by `$t`. Animate it with `steps` steps.
+    pub fn scad(&self) -> String {
+        let frames = (0..self.steps)
+            .map(|ii| format!("if (frame == {}) {{\n  {}\n}}", ii, self.frame(ii).indent()))
+            .collect::<Vec<_>>().join("\n");
+        format!("// Animate with Steps = {}\nframe = round($t * {});\n{}", self.steps, self.steps, frames)
+    }
+
+    /// Write each frame to `dir/name_0000.scad` and so on, returning the paths written.
+    pub fn write_frames<P: AsRef<Path>>(&self, dir: P, name: &str) -> std::io::Result<Vec<PathBuf>> {
+        (0..self.steps).map(|ii| {
+            let path = dir.as_ref().join(format!("{}_{:04}.scad", name, ii));
+            std::fs::write(&path, self.frame(ii).scad())?;
+            Ok(path)
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keyframes() {
+        let slide = Keyframes::Translate(vec![(0., v3(0, 0, 10)), (0.5, v3(0, 0, 0)), (1., v3(4, 0, 0))]);
+        assert_eq!(slide.at(0.25), v3(0, 0, 5));
+        assert_eq!(slide.at(0.75), v3(2, 0, 0));
+        assert_eq!(slide.at(2.), v3(4, 0, 0));
+        assert_eq!(D3::cube(1).animate_translate(vec![(0., v3(0, 0, 0)), (1., v3(5, 0, 0))]).scad(),
+            "translate(v = [lookup($t, [[0, 0], [1, 5]]), lookup($t, [[0, 0], [1, 0]]), lookup($t, [[0, 0], [1, 0]])]) {\n  cube(size = 1);\n}"
+        );
+    }
+
+    #[test]
+    fn test_animation() {
+        let animation = Animation::new(2, |t| D3::cube(1).translate(v3(0, 0, 4. * t)));
+        assert_eq!(animation.scad(),
+            "// Animate with Steps = 2\nframe = round($t * 2);\nif (frame == 0) {\n  translate(v = [0, 0, 0]) {\n    cube(size = 1);\n  }\n}\nif (frame == 1) {\n  translate(v = [0, 0, 2]) {\n    cube(size = 1);\n  }\n}"
+        );
+    }
+}
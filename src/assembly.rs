@@ -0,0 +1,102 @@
+//! Parts placed together into an assembled model, with exploded views and print layouts.
+
+use crate::*;
+use anyhow::Result;
+
+/// A named part modeled in its print orientation with its placement in the assembly.
+/// The part is rotated by `rotate` and then translated by `translate` to assemble it.
+#[derive(Clone, Debug)]
+pub struct Part {
+    pub name: String,
+    pub color: ColorEnum,
+    pub shape: D3,
+    pub rotate: XYZ,
+    pub translate: XYZ,
+}
+
+impl Part {
+    /// The part moved to its place in the assembly.
+    pub fn placed(&self) -> D3 {
+        let shape = if self.rotate == v3(0, 0, 0) { self.shape.clone() } else { self.shape.clone().rotate(self.rotate) };
+        shape.translate(self.translate)
+    }
+
+    /// The part in its print orientation, labeled with its name.
+    pub fn labeled(&self) -> D3 {
+        self.shape.clone().named(&self.name)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Assembly {
+    pub parts: Vec<Part>,
+}
+
+impl Assembly {
+    pub fn new() -> Assembly {
+        Assembly { parts: vec![] }
+    }
+
+    /// Add a part rotated by `rotate` and then translated by `translate` into place.
+    pub fn add(mut self, name: &str, color: ColorEnum, shape: D3, rotate: XYZ, translate: XYZ) -> Assembly {
+        self.parts.push(Part { name: name.to_string(), color, shape, rotate, translate });
+        self
+    }
+
+    /// All parts in place, each in its color and labeled with its name.
+    pub fn assembled(&self) -> D3 {
+        self.exploded(0.)
+    }
+
+    /// All parts pushed away from the centroid of the assembly by `factor` times their distance from it.
+    pub fn exploded(&self, factor: f32) -> D3 {
+        let centers: Vec<XYZ> = self.parts.iter().map(|part| part.placed().bounds().center()).collect();
+        let n = centers.len().max(1) as f32;
+        let centroid = centers.iter().fold(v3(0, 0, 0), |sum, c| sum + *c);
+        let centroid = v3(centroid.0 / n, centroid.1 / n, centroid.2 / n);
+        self.parts.iter().zip(centers).map(|(part, center)| {
+            let shape = part.placed();
+            let shape = if factor == 0. {
+                shape
+            } else {
+                let XYZ(x, y, z) = center - centroid;
+                shape.translate(v3(x * factor, y * factor, z * factor))
+            };
+            shape.color(part.color.clone()).named(&part.name)
+        }).union()
+    }
+
+    /// The parts in their print orientation packed onto plates by `layout`.
+    pub fn print(&self, layout: &Layout) -> Result<Vec<D3>> {
+        layout.plates(self.parts.iter().map(Part::labeled).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_assembled() {
+        let assembly = Assembly::new()
+            .add("base", ColorEnum::Blue, D3::cuboid(v3(4, 4, 1)), v3(0, 0, 0), v3(0, 0, 0))
+            .add("post", ColorEnum::Red, D3::cube(2), v3(0, 0, 0), v3(1, 1, 1));
+        assert_eq!(assembly.assembled().scad(),
+            "union() {\n  // base\n  color(\"blue\") {\n    translate(v = [0, 0, 0]) {\n      cube(size = [4, 4, 1]);\n    }\n  }\n  // post\n  color(\"red\") {\n    translate(v = [1, 1, 1]) {\n      cube(size = 2);\n    }\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn test_exploded() {
+        let assembly = Assembly::new()
+            .add("bottom", ColorEnum::Blue, D3::cube(2), v3(0, 0, 0), v3(0, 0, 0))
+            .add("top", ColorEnum::Red, D3::cube(2), v3(180, 0, 0), v3(0, 2, 4));
+        let exploded = assembly.exploded(1.);
+        assert_eq!(exploded.bounds().min.2, -1.);
+        assert_eq!(exploded.bounds().max.2, 5.);
+        let plates = assembly.print(&Layout::new(v2(10, 10), 1)).unwrap();
+        assert_eq!(plates.len(), 1);
+        assert!(plates[0].find_named("top").is_some());
+        assert!(assembly.assembled().find_named("bottom").is_some());
+    }
+}
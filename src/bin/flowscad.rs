@@ -0,0 +1,88 @@
+// Driver that writes every registered model, and every other `*-scad` binary next to this executable,
+// into an output directory.
+// Usage: flowscad [--out DIR] [--no-openscad] [--timeout SECONDS] [MODEL...]
+
+use anyhow::{anyhow, bail, Result};
+use flowscad::*;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+fn report(steps: &[Step]) {
+    for step in steps {
+        match &step.error {
+            None => eprintln!("{} {:.2}s", step.file.display(), step.seconds),
+            Some(error) => eprintln!("{} FAILED: {}", step.file.display(), error),
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let mut out_dir = "scad".to_string();
+    let mut use_openscad = true;
+    let mut timeout = None;
+    let mut only = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out_dir = args.next().ok_or_else(|| anyhow!("--out needs a directory"))?,
+            "--no-openscad" => use_openscad = false,
+            "--timeout" => {
+                let seconds: f64 = args.next().ok_or_else(|| anyhow!("--timeout needs a number of seconds"))?.parse()?;
+                timeout = Some(Duration::from_secs_f64(seconds));
+            },
+            _ if arg.starts_with("--") => bail!("unknown option {}", arg),
+            _ => only.push(arg),
+        }
+    }
+
+    let registry = Registry::builtin();
+    let mut renderer = Renderer::new(&out_dir);
+    if !use_openscad {
+        renderer.openscad = None;
+    }
+    if timeout.is_some() {
+        renderer.timeout = timeout;
+    }
+    for model in &registry.models {
+        if !only.is_empty() && !only.iter().any(|name| name == model.name()) {
+            continue;
+        }
+        let start = Instant::now();
+        let scad = model.scad();
+        let steps = renderer.render(model.name(), &scad, start.elapsed().as_secs_f64());
+        report(steps);
+    }
+
+    let bin_dir = std::env::current_exe()?.parent().unwrap().to_path_buf();
+    let mut models: Vec<(String, std::path::PathBuf)> = std::fs::read_dir(&bin_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().unwrap_or_default() == std::env::consts::EXE_EXTENSION)
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.strip_suffix("-scad")?.to_string();
+            Some((name, path))
+        })
+        .filter(|(name, _)| registry.get(name).is_none() && (only.is_empty() || only.contains(name)))
+        .collect();
+    models.sort();
+
+    for (name, path) in models {
+        let start = Instant::now();
+        let output = output_within(&mut Command::new(&path), renderer.timeout);
+        let seconds = start.elapsed().as_secs_f64();
+        match output {
+            Ok(output) if output.status.success() => {
+                report(renderer.render(&name, &String::from_utf8_lossy(&output.stdout), seconds));
+            },
+            Ok(output) => {
+                eprintln!("{} FAILED: {}", name, output.status);
+                renderer.failed(&name, seconds, String::from_utf8_lossy(&output.stderr).trim().to_string());
+            },
+            Err(error) => {
+                eprintln!("{} FAILED: {}", name, error);
+                renderer.failed(&name, seconds, error.to_string());
+            },
+        }
+    }
+    eprintln!("{}", renderer.write_manifest()?.display());
+    Ok(())
+}
@@ -1,20 +1,10 @@
+use anyhow::Result;
 use flowscad::*;
 
-fn main() {
-    let scale = 5.;  // Should be 60mm G4G `G`
-    let sq3 = f32::powf(3.0, 0.5);
-    let logo = D2::polygon(vec![
-                    v2(0,0), v2(sq3,1), v2(4.*sq3,1.), v2(4.*sq3,-4),
-                    v2(0,-8), v2(-4.*sq3,-4), v2(-4.*sq3,4),
-                    v2(0,8), v2(4.*sq3,4), v2(3.*sq3,3),
-                    v2(0,6), v2(-3.*sq3,3), v2(-3.*sq3,-3),
-                    v2(0,-6), v2(3.*sq3,-3), v2(3.*sq3,-1), v2(sq3,-1),
-        ])
-        .scale(scale)
-        .linear_extrude(10)
-        ;
-    println!("{}", &logo.scad());
+fn main() -> Result<()> {
+    run(&G4gLogo)
 }
+
 /*
 
 if __name__ == '__main__':
@@ -3,12 +3,12 @@ use flowscad::*;
 
 fn invertible_noam_arc<T: Into<X>>(diameter: T) -> D3 {
     let d: X = diameter.into();
-    D2::circle(d)
+    D2::circle(&d)
         // .intersection(D2::half_plane(Aim::N))
         .and(D2::half_plane(Aim::N))
-        .add(D2::circle(d/3).translate( (d/3,0) ))
-        .add(D2::circle(d/3).translate( (-d/3,0) ))
-        .sub(D2::circle(d/3))
+        .add(D2::circle(&d/3).translate( (&d/3,0) ))
+        .add(D2::circle(&d/3).translate( (-&d/3,0) ))
+        .sub(D2::circle(&d/3))
         .linear_extrude(5)
 }
 
@@ -1,6 +1,7 @@
+use anyhow::Result;
 use flowscad::*;
 
-fn main() {
+fn main() -> Result<()> {
     let s = 80.;  // base edge length of pyramid
     // Full Pyramid 2x2 ratio base
     let pyramid = D3::cuboid(v3(s, s, 0.01))
@@ -30,13 +31,9 @@ fn main() {
     // Square base to hold puzzle in
     let base = D3::cuboid(v3(s+4., s+4., 4)).difference(D3::cube(s).translate(v3(2,2,2)));
     // Lay all the pieces out for printing
-    let puzzle = piece1
-        .add(base.translate(v3(-s*1.2+5., -s/2., 0.)) )
-        .add(piece2.translate(v3(0., s*0.6+5., 0.)))
-        .add(piece3.translate(v3(-s * 0.6+5., s*0.2, 0.)))
-        .add(piece4.clone().translate(v3(0., -s*0.6, 0.)))
-        .add(piece4.translate(v3(-s * 0.75+5., -s*0.6, 0.)))
-        ;
+    let puzzle = Layout::new(v2(220, 220), 5)
+        .arrange(vec![base, piece1, piece2, piece3, piece4.clone(), piece4])?;
 
     println!("{}", &puzzle);
+    Ok(())
 }
@@ -4,7 +4,7 @@ pub fn beveled_truncated_octahedron(l_edge: f32) -> D3 {
     //* Create a beveled truncated ocatahedron with edge length `l_edge` centered at the origin
     let bevel = 0.5;
     let r_square = 2.0_f32.powf(0.5) * l_edge;  // height of truncated octahedron between square faces
-    D3::Hull(Box::new(vec![
+    D3::join(BoolOp::Hull, vec![
         D3::beveled_box(v3(l_edge, l_edge, 2.0*r_square), bevel)
             .translate(v3(-l_edge/2.0, -l_edge/2.0, -r_square))
             .rotate(v3(0., 0., 45.)),
@@ -14,7 +14,7 @@ pub fn beveled_truncated_octahedron(l_edge: f32) -> D3 {
         D3::beveled_box(v3(2.*r_square, l_edge, l_edge), bevel)
             .translate(v3(-r_square, -l_edge/2.0, -l_edge/2.0))
             .rotate(v3(45., 0., 0.)),
-        ]))
+        ])
 }
 
 fn main() {
@@ -5,11 +5,9 @@ fn main() {
     let r_square = 2.0_f64.powf(0.5) * l_edge;  // height of truncated octahedron between square faces
     let gap = 0.1;
     let result = D3::truncated_octahedron(l_edge + gap)
-        .add_map(move |x| x.translate(v3(2.0*r_square,0,0)))
-        .add_map(move |x| x.translate(v3(0,2.0*r_square,0)))
-        .invert(100.)
-        .minkowski(D3::truncated_octahedron(2.0*gap))
-        .invert(99.)
+        .iter_lattice(&Lattice::cubic(2.0*r_square), (2, 2, 1))
+        .union()
+        .shrink(D3::truncated_octahedron(2.0*gap))
         .add_map(move |x| x.translate(v3(12.0*r_square,0,0)))
         .add_map(move |x| x.translate(v3(0,12.0*r_square,0)))
         ;
@@ -0,0 +1,175 @@
+//! Bill of materials for the named parts of a `D3` tree.
+
+use crate::*;
+
+/// Density of PLA in grams per cubic centimeter.
+pub const PLA_DENSITY: f32 = 1.24;
+
+/// One kind of part, with the count of identical copies and measurements of a single copy in mm and grams.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BomLine {
+    pub name: String,
+    pub quantity: usize,
+    pub volume: Option<f32>,
+    pub size: XYZ,
+    pub mass: Option<f32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bom {
+    pub lines: Vec<BomLine>,
+}
+
+impl D3 {
+    /// The shape without the wrappers that only place, orient or label it in a model: translations,
+    /// rotations, animations and names. Mirrors and colors are kept since they make a different part.
+    pub fn unplaced(&self) -> &D3 {
+        match self {
+            D3::Translate(_, shape) | D3::Rotate(_, shape) | D3::Animate(_, shape) | D3::Named(_, shape)
+                => shape.unplaced(),
+            shape => shape,
+        }
+    }
+
+    /// The outermost named subtrees, depth first.
+    pub fn parts(&self) -> Vec<(&Meta, &D3)> {
+        match self {
+            D3::Named(meta, shape) if !meta.name.is_empty() => vec![(meta, shape)],
+            shape => shape.children().into_iter().flat_map(|child| child.parts()).collect(),
+        }
+    }
+}
+
+impl Bom {
+    /// Group the named parts of `shape` that are identical up to placement (not mirroring or color), counting a `quantity`
+    /// label as that many copies. Masses use `density` in g/cm³.
+    pub fn new(shape: &D3, density: f32) -> Bom {
+        let mut lines: Vec<(u64, BomLine)> = vec![];
+        for (meta, part) in shape.parts() {
+            let part = part.unplaced();
+            let quantity = meta.get("quantity").and_then(|q| q.parse().ok()).unwrap_or(1);
+            let key = part.fingerprint();
+            match lines.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, line)) => line.quantity += quantity,
+                None => {
+                    let volume = part.volume();
+                    lines.push((key, BomLine {
+                        name: meta.name.clone(),
+                        quantity,
+                        volume,
+                        size: part.bounds().size(),
+                        mass: volume.map(|v| v / 1000. * density),
+                    }));
+                },
+            }
+        }
+        Bom { lines: lines.into_iter().map(|(_, line)| line).collect() }
+    }
+
+    /// Mass of all copies of all parts, if every part has a known volume.
+    pub fn total_mass(&self) -> Option<f32> {
+        self.lines.iter().map(|line| line.mass.map(|m| m * line.quantity as f32)).sum()
+    }
+
+    fn cells(line: &BomLine) -> [String; 5] {
+        let known = |v: Option<f32>| v.map(|v| format!("{:.1}", v)).unwrap_or_default();
+        [
+            line.name.clone(),
+            line.quantity.to_string(),
+            known(line.volume),
+            format!("{:.1} x {:.1} x {:.1}", line.size.0, line.size.1, line.size.2),
+            known(line.mass),
+        ]
+    }
+
+    pub fn markdown(&self) -> String {
+        let mut rows = vec![
+            "| Part | Quantity | Volume (mm³) | Size (mm) | Mass (g) |".to_string(),
+            "|---|---:|---:|---|---:|".to_string(),
+        ];
+        rows.extend(self.lines.iter().map(|line| format!("| {} |", Bom::cells(line).join(" | "))));
+        rows.join("\n")
+    }
+
+    /// Comma separated values, quoting cells as RFC 4180 requires.
+    pub fn csv(&self) -> String {
+        let quote = |cell: String| if cell.contains([',', '"', '\r', '\n']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell
+        };
+        let mut rows = vec!["part,quantity,volume,size,mass".to_string()];
+        rows.extend(self.lines.iter().map(|line| Bom::cells(line).map(quote).join(",")));
+        rows.join("\n")
+    }
+
+    pub fn json(&self) -> String {
+        serde_json::json!({
+            "parts": self.lines.iter().map(|line| serde_json::json!({
+                "name": line.name,
+                "quantity": line.quantity,
+                "volume": line.volume,
+                "size": [line.size.0, line.size.1, line.size.2],
+                "mass": line.mass,
+            })).collect::<Vec<_>>(),
+            "total_mass": self.total_mass(),
+        }).to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tetrahedron() -> D3 {
+        D3::Polyhedron(
+            Box::new(vec![[0., 0., 0.], [10., 0., 0.], [0., 10., 0.], [0., 0., 10.]]),
+            Box::new(vec![Box::new(vec![0, 1, 2]), Box::new(vec![0, 3, 1]), Box::new(vec![0, 2, 3]), Box::new(vec![1, 3, 2])]),
+        )
+    }
+
+    #[test]
+    fn test_volume() {
+        assert!((tetrahedron().volume().unwrap() - 1000. / 6.).abs() < 1e-3);
+        assert!((tetrahedron().scale(2).translate(v3(5, 5, 5)).volume().unwrap() - 8000. / 6.).abs() < 1e-2);
+        assert_eq!(D3::cube(1).difference(tetrahedron()).volume(), None);
+    }
+
+    #[test]
+    fn test_bom() {
+        let kit = tetrahedron().named("corner").translate(v3(20, 0, 0))
+            .add(tetrahedron().rotate(v3(0, 0, 90)).named("corner"))
+            .add(D3::cube(4).named("peg").with_meta("quantity", "6"))
+            .add(D3::cube(2).difference(D3::sphere(1)).named("cap"));
+        let bom = Bom::new(&kit, PLA_DENSITY);
+        assert_eq!(bom.lines.len(), 3);
+        assert_eq!((bom.lines[0].quantity, bom.lines[1].quantity), (2, 6));
+        assert_eq!(bom.total_mass(), None);
+        assert_eq!(bom.csv(), "part,quantity,volume,size,mass\ncorner,2,166.7,10.0 x 10.0 x 10.0,0.2\npeg,6,64.0,4.0 x 4.0 x 4.0,0.1\ncap,1,,2.0 x 2.0 x 2.0,");
+        assert_eq!(bom.markdown().lines().nth(2), Some("| corner | 2 | 166.7 | 10.0 x 10.0 x 10.0 | 0.2 |"));
+        assert!(bom.json().starts_with(r#"{"parts":[{"mass":0.2066"#));
+    }
+
+    #[test]
+    fn test_unplaced() {
+        let kit = D3::cube(3).named("block, \"left\"")
+            .add(D3::cube(3).translate(v3(5, 0, 0)).named("block, \"left\""))
+            .add(D3::cube(3).animate_rotate(vec![(0., v3(0, 0, 0)), (1., v3(0, 0, 90))]).named("lid").named("block, \"left\""));
+        let bom = Bom::new(&kit, PLA_DENSITY);
+        assert_eq!(bom.lines.len(), 1);
+        assert_eq!(bom.lines[0].quantity, 3);
+        assert_eq!(bom.csv().lines().nth(1), Some(r#""block, ""left""",3,27.0,3.0 x 3.0 x 3.0,0.0"#));
+    }
+
+    #[test]
+    fn test_chiral() {
+        // Arms of three different lengths along X, Y and Z, so no rotation matches its mirror image.
+        let l = D3::cuboid(v3(3, 1, 1)).add(D3::cuboid(v3(1, 2, 1))).add(D3::cuboid(v3(1, 1, 4)));
+        let kit = l.clone().named("bracket")
+            .add(l.clone().rotate(v3(0, 0, 90)).named("bracket"))
+            .add(l.clone().mirror(v3(1, 0, 0)).named("bracket"))
+            .add(l.color(ColorEnum::Red).named("bracket"));
+        let bom = Bom::new(&kit, PLA_DENSITY);
+        assert_eq!(bom.lines.iter().map(|line| line.quantity).collect::<Vec<_>>(), vec![2, 1, 1]);
+    }
+}
@@ -0,0 +1,273 @@
+//! Axis aligned bounding boxes for `D2` and `D3` shapes.
+
+use crate::*;
+
+/// Axis aligned bounding box in the XY plane.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BBox2 {
+    pub min: XY,
+    pub max: XY,
+}
+
+/// Axis aligned bounding box in space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BBox3 {
+    pub min: XYZ,
+    pub max: XYZ,
+}
+
+impl BBox2 {
+    /// The box containing nothing. It is the identity for `union`.
+    pub const EMPTY: BBox2 = BBox2 {
+        min: XY(f32::INFINITY, f32::INFINITY),
+        max: XY(f32::NEG_INFINITY, f32::NEG_INFINITY),
+    };
+
+    pub fn new(min: XY, max: XY) -> BBox2 {
+        BBox2 { min, max }
+    }
+
+    /// Smallest box containing all of `points`.
+    pub fn from_points<I: IntoIterator<Item = XY>>(points: I) -> BBox2 {
+        points.into_iter().fold(BBox2::EMPTY, |b, p| BBox2 {
+            min: XY(b.min.0.min(p.0), b.min.1.min(p.1)),
+            max: XY(b.max.0.max(p.0), b.max.1.max(p.1)),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.0 > self.max.0 || self.min.1 > self.max.1
+    }
+
+    pub fn size(&self) -> XY {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> XY {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn corners(&self) -> [XY; 4] {
+        [self.min, XY(self.max.0, self.min.1), self.max, XY(self.min.0, self.max.1)]
+    }
+
+    pub fn union(self, other: BBox2) -> BBox2 {
+        BBox2::from_points([self.min, self.max, other.min, other.max].into_iter()
+            .filter(|p| p.0.is_finite() && p.1.is_finite()))
+    }
+
+    pub fn intersection(self, other: BBox2) -> BBox2 {
+        let b = BBox2 {
+            min: XY(self.min.0.max(other.min.0), self.min.1.max(other.min.1)),
+            max: XY(self.max.0.min(other.max.0), self.max.1.min(other.max.1)),
+        };
+        if b.is_empty() { BBox2::EMPTY } else { b }
+    }
+
+    /// Bounding box of the Minkowski sum of the boxed shapes.
+    pub fn minkowski(self, other: BBox2) -> BBox2 {
+        if self.is_empty() || other.is_empty() { return BBox2::EMPTY; }
+        BBox2 { min: self.min + other.min, max: self.max + other.max }
+    }
+
+    /// Grow the box by `d` on every side.
+    pub fn pad(self, d: f32) -> BBox2 {
+        if self.is_empty() { return self; }
+        BBox2 { min: self.min - XY(d, d), max: self.max + XY(d, d) }
+    }
+
+    /// Box around the image of the corners under the linear map `f`.
+    pub fn map<F: Fn(XY) -> XY>(self, f: F) -> BBox2 {
+        if self.is_empty() { return self; }
+        BBox2::from_points(self.corners().into_iter().map(f))
+    }
+}
+
+impl BBox3 {
+    /// The box containing nothing. It is the identity for `union`.
+    pub const EMPTY: BBox3 = BBox3 {
+        min: XYZ(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        max: XYZ(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+    };
+
+    pub fn new(min: XYZ, max: XYZ) -> BBox3 {
+        BBox3 { min, max }
+    }
+
+    /// Smallest box containing all of `points`.
+    pub fn from_points<I: IntoIterator<Item = XYZ>>(points: I) -> BBox3 {
+        points.into_iter().fold(BBox3::EMPTY, |b, p| BBox3 {
+            min: XYZ(b.min.0.min(p.0), b.min.1.min(p.1), b.min.2.min(p.2)),
+            max: XYZ(b.max.0.max(p.0), b.max.1.max(p.1), b.max.2.max(p.2)),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.0 > self.max.0 || self.min.1 > self.max.1 || self.min.2 > self.max.2
+    }
+
+    pub fn size(&self) -> XYZ {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> XYZ {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn corners(&self) -> [XYZ; 8] {
+        let (a, b) = (self.min, self.max);
+        [XYZ(a.0, a.1, a.2), XYZ(b.0, a.1, a.2), XYZ(b.0, b.1, a.2), XYZ(a.0, b.1, a.2),
+         XYZ(a.0, a.1, b.2), XYZ(b.0, a.1, b.2), XYZ(b.0, b.1, b.2), XYZ(a.0, b.1, b.2)]
+    }
+
+    pub fn union(self, other: BBox3) -> BBox3 {
+        BBox3::from_points([self.min, self.max, other.min, other.max].into_iter()
+            .filter(|p| p.0.is_finite() && p.1.is_finite() && p.2.is_finite()))
+    }
+
+    pub fn intersection(self, other: BBox3) -> BBox3 {
+        let b = BBox3 {
+            min: XYZ(self.min.0.max(other.min.0), self.min.1.max(other.min.1), self.min.2.max(other.min.2)),
+            max: XYZ(self.max.0.min(other.max.0), self.max.1.min(other.max.1), self.max.2.min(other.max.2)),
+        };
+        if b.is_empty() { BBox3::EMPTY } else { b }
+    }
+
+    /// Bounding box of the Minkowski sum of the boxed shapes.
+    pub fn minkowski(self, other: BBox3) -> BBox3 {
+        if self.is_empty() || other.is_empty() { return BBox3::EMPTY; }
+        BBox3 { min: self.min + other.min, max: self.max + other.max }
+    }
+
+    /// Grow the box by `d` on every side.
+    pub fn pad(self, d: f32) -> BBox3 {
+        if self.is_empty() { return self; }
+        BBox3 { min: self.min - XYZ(d, d, d), max: self.max + XYZ(d, d, d) }
+    }
+
+    /// Box around the image of the corners under the linear map `f`.
+    pub fn map<F: Fn(XYZ) -> XYZ>(self, f: F) -> BBox3 {
+        if self.is_empty() { return self; }
+        BBox3::from_points(self.corners().into_iter().map(f))
+    }
+
+    /// A cuboid filling the box.
+    pub fn cuboid(&self) -> D3 {
+        D3::cuboid(self.size()).translate(self.min)
+    }
+}
+
+impl D2 {
+    /// Conservative bounding box of the shape. Exact for primitives and polygons.
+    pub fn bounds(&self) -> BBox2 {
+        match self {
+            D2::Circle(X(d, _)) => BBox2::new(v2(-d/2., -d/2.), v2(d/2., d/2.)),
+            D2::Square(X(s, _)) => BBox2::from_points([v2(0, 0), v2(*s, *s)]),
+            D2::Rectangle(xy) => BBox2::from_points([v2(0, 0), *xy]),
+            D2::Polygon(points) => BBox2::from_points(points.iter().copied()),
+            D2::Color(_, shape) => shape.bounds(),
+            D2::Rotate(X(theta, _), shape) => shape.bounds().map(|p| p.rotate(*theta)),
+            D2::Scale(X(s, _), shape) => shape.bounds().map(|p| p * *s),
+            D2::Scale2(XY(x, y), shape) => shape.bounds().map(|p| XY(p.0 * x, p.1 * y)),
+            D2::Translate(xy, shape) => shape.bounds().map(|p| p + *xy),
+            D2::Mirror(n, shape) => shape.bounds().map(|p| p - *n * (2. * p.dot(*n) / n.dot(*n))),
+            D2::Join(BoolOp::Intersection, v) => v.iter().map(|x| x.bounds())
+                .reduce(|a, b| a.intersection(b)).unwrap_or(BBox2::EMPTY),
+            D2::Join(BoolOp::Minkowski, v) => v.iter().map(|x| x.bounds())
+                .reduce(|a, b| a.minkowski(b)).unwrap_or(BBox2::EMPTY),
+            D2::Join(_, v) => v.iter().fold(BBox2::EMPTY, |a, x| a.union(x.bounds())),
+            D2::Difference(shape, _) => shape.bounds(),
+        }
+    }
+}
+
+impl D3 {
+    /// Conservative bounding box of the shape. Exact for primitives and polyhedra.
+    pub fn bounds(&self) -> BBox3 {
+        match self {
+            D3::Cube(X(s, _)) => BBox3::from_points([v3(0, 0, 0), v3(*s, *s, *s)]),
+            D3::Cuboid(xyz) => BBox3::from_points([v3(0, 0, 0), *xyz]),
+            D3::Sphere(X(r, _)) => BBox3::new(v3(-r, -r, -r), v3(*r, *r, *r)),
+            D3::Cylinder(X(h, _), X(r, _)) => BBox3::from_points([v3(-r, -r, 0), v3(*r, *r, *h)]),
+            D3::Polyhedron(points, _) => BBox3::from_points(points.iter().map(|p| XYZ(p[0], p[1], p[2]))),
+            D3::Color(_, shape) => shape.bounds(),
+            D3::Translate(xyz, shape) => shape.bounds().map(|p| p + *xyz),
+            D3::Rotate(xyz, shape) => shape.bounds().map(|p| p.rotate(*xyz)),
+            D3::Mirror(n, shape) => shape.bounds().map(|p| p - *n * (2. * p.dot(*n) / n.dot(*n))),
+            D3::Scale(X(s, _), shape) => shape.bounds().map(|p| p * *s),
+            D3::Scale3(XYZ(x, y, z), shape) => shape.bounds().map(|p| XYZ(p.0 * x, p.1 * y, p.2 * z)),
+            D3::LinearExtrude(X(h, _), shape) => {
+                let b = shape.bounds();
+                if b.is_empty() { return BBox3::EMPTY; }
+                BBox3::from_points([v3(b.min.0, b.min.1, 0), v3(b.max.0, b.max.1, *h)])
+            },
+            D3::RotateExtrude(_, shape) => {
+                let b = shape.bounds();
+                if b.is_empty() { return BBox3::EMPTY; }
+                let r = b.min.0.abs().max(b.max.0.abs());
+                BBox3::new(v3(-r, -r, b.min.1), v3(r, r, b.max.1))
+            },
+            D3::Join(BoolOp::Intersection, v) => v.iter().map(|x| x.bounds())
+                .reduce(|a, b| a.intersection(b)).unwrap_or(BBox3::EMPTY),
+            D3::Join(BoolOp::Minkowski, v) => v.iter().map(|x| x.bounds())
+                .reduce(|a, b| a.minkowski(b)).unwrap_or(BBox3::EMPTY),
+            D3::Join(_, v) => v.iter().fold(BBox3::EMPTY, |a, x| a.union(x.bounds())),
+            D3::Difference(shape, _) | D3::Named(_, shape) => shape.bounds(),
+            D3::Animate(Keyframes::Translate(keys), shape) => {
+                let b = shape.bounds();
+                keys.iter().fold(BBox3::EMPTY, |a, (_, xyz)| a.union(b.map(|p| p + *xyz)))
+            },
+            D3::Animate(Keyframes::Rotate(_), shape) => {
+                // Any rotation about the origin stays within the sphere through the farthest corner.
+                let b = shape.bounds();
+                if b.is_empty() { return BBox3::EMPTY; }
+                let r = b.corners().iter().map(|p| p.norm()).fold(0., f32::max);
+                BBox3::new(v3(-r, -r, -r), v3(r, r, r))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_near(a: BBox3, b: BBox3) {
+        let d = (a.min - b.min).norm() + (a.max - b.max).norm();
+        assert!(d < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_primitive_bounds() {
+        assert_eq!(D3::cube(2).bounds(), BBox3::new(v3(0, 0, 0), v3(2, 2, 2)));
+        assert_eq!(D3::cylinder(3, 1).bounds(), BBox3::new(v3(-1, -1, 0), v3(1, 1, 3)));
+        assert_eq!(D2::circle(4).bounds(), BBox2::new(v2(-2, -2), v2(2, 2)));
+        assert_eq!(D2::polygon(vec![v2(0, 1), v2(-2, 3), v2(4, -1)]).bounds(), BBox2::new(v2(-2, -1), v2(4, 3)));
+    }
+
+    #[test]
+    fn test_transform_bounds() {
+        assert_near(D3::cuboid(v3(1, 2, 3)).rotate(v3(0, 0, 90)).translate(v3(10, 0, 0)).bounds(),
+            BBox3::new(v3(8, 0, 0), v3(10, 1, 3)));
+        assert_near(D3::cube(1).scale3(v3(-1, 2, 1)).bounds(), BBox3::new(v3(-1, 0, 0), v3(0, 2, 1)));
+        assert_eq!(D2::square(2).mirror(v2(1, 0)).bounds(), BBox2::new(v2(-2, 0), v2(0, 2)));
+    }
+
+    #[test]
+    fn test_boolean_bounds() {
+        let a = D3::cube(2);
+        let b = D3::sphere(1);
+        assert_eq!((a.clone() + b.clone()).bounds(), BBox3::new(v3(-1, -1, -1), v3(2, 2, 2)));
+        assert_eq!(a.clone().intersection(b.clone()).bounds(), BBox3::new(v3(0, 0, 0), v3(1, 1, 1)));
+        assert_eq!(a.clone().minkowski(b.clone()).bounds(), BBox3::new(v3(-1, -1, -1), v3(3, 3, 3)));
+        assert_eq!((a - b).bounds(), BBox3::new(v3(0, 0, 0), v3(2, 2, 2)));
+        assert!(D3::cube(1).intersection(D3::cube(1).translate(v3(5, 0, 0))).bounds().is_empty());
+    }
+
+    #[test]
+    fn test_extrude_bounds() {
+        assert_eq!(D2::square(2).translate(v2(3, 0)).rotate_extrude(360).bounds(),
+            BBox3::new(v3(-5, -5, 0), v3(5, 5, 2)));
+        assert_eq!(D2::circle(2).linear_extrude(5).bounds(), BBox3::new(v3(-1, -1, 0), v3(1, 1, 5)));
+    }
+}
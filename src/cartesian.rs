@@ -1,174 +1,290 @@
 use std::ops::*;
+use std::sync::Arc;
 
 use derive_more::*;
 
-pub const PI: X = X(std::f32::consts::PI);
+pub const PI: X = X(std::f32::consts::PI, None);
 
-#[derive(Clone, Copy, PartialEq, Neg)]
-pub struct X(pub f32);
+/// A number, with the SCAD expression it was computed from when it depends on a `Param`.
+/// Plain numbers are made with `X::new` or `X::from`, expressions only come from `Param::x`.
+/// Equality compares the value and the expression, see `hash.rs`.
+#[derive(Clone)]
+pub struct X(pub f32, pub(crate) Option<Arc<str>>);
 
 impl X {
     /// Positive X MAX is lower since it is used for super large objects that could be shifted or rotated.
-    pub const MAX: X = X(f32::MAX/1000.0);
+    pub const MAX: X = X(f32::MAX/1000.0, None);
 
-    pub fn atan(self) -> Self {
-        X(self.0.atan())
+    pub const fn new(value: f32) -> X {
+        X(value, None)
     }
 
-    pub fn cos(self) -> Self {
-        X(self.0.cos())
+    /// The SCAD expression of the number, if it depends on a `Param`.
+    pub fn expr(&self) -> Option<&str> {
+        self.1.as_deref()
+    }
+
+    pub fn atan(&self) -> Self {
+        X(self.0.atan(), None)
+    }
+
+    pub fn cos(&self) -> Self {
+        X(self.0.cos(), None)
+    }
+
+    /// Operand text for building expressions, parenthesized unless it is a single term.
+    fn term(&self) -> String {
+        match self.expr() {
+            Some(expr) if expr.contains(' ') => format!("({})", expr),
+            Some(expr) => expr.to_string(),
+            None => format!("{}", self.0),
+        }
+    }
+
+    /// Combine `a` and `b` with the SCAD operator `op` into the already computed `value`.
+    fn combine(a: &X, op: &str, b: &X, value: f32) -> X {
+        if a.1.is_none() && b.1.is_none() {
+            X(value, None)
+        } else {
+            X(value, Some(format!("{} {} {}", a.term(), op, b.term()).into()))
+        }
+    }
+}
+
+impl std::ops::Neg for &X {
+    type Output = X;
+    fn neg(self) -> X {
+        match self.1 {
+            None => X(-self.0, None),
+            Some(_) => X(-self.0, Some(format!("-{}", self.term()).into())),
+        }
+    }
+}
+
+impl std::ops::Neg for X {
+    type Output = X;
+    fn neg(self) -> X {
+        -&self
     }
 }
 
 impl std::fmt::Debug for X {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", &self.0)
+        match self.expr() {
+            Some(expr) => write!(f, "{} = {}", expr, &self.0),
+            None => write!(f, "{}", &self.0),
+        }
     }
 }
 
+/// SCAD text of the number, its expression if it has one.
 impl std::fmt::Display for X {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", &self.0)
+        match self.expr() {
+            Some(expr) => write!(f, "{}", expr),
+            None => write!(f, "{}", &self.0),
+        }
     }
 }
 
 impl std::ops::AddAssign for X {
     fn add_assign(&mut self, other: Self) {
-        *self = Self(self.0 + other.0);
+        *self = self.clone() + other;
     }
 }
 
 impl std::ops::SubAssign for X {
     fn sub_assign(&mut self, other: Self) {
-        *self = Self(self.0 - other.0);
+        *self = self.clone() - other;
     }
 }
 
 // TODO: Macro to replace all this?
 impl From<u32> for X {
     fn from(i: u32) -> X {
-        X(i as f32)
+        X(i as f32, None)
     }
 }
 
 impl From<i32> for X {
     fn from(i: i32) -> X {
-        X(i as f32)
+        X(i as f32, None)
     }
 }
 
 impl From<u64> for X {
     fn from(i: u64) -> X {
-        X(i as f32)
+        X(i as f32, None)
     }
 }
 
 impl From<i64> for X {
     fn from(i: i64) -> X {
-        X(i as f32)
+        X(i as f32, None)
     }
 }
 
 impl From<f32> for X {
     fn from(f: f32) -> X {
-        X(f as f32)
+        X(f as f32, None)
     }
 }
 
 impl From<f64> for X {
     fn from(f: f64) -> X {
-        X(f as f32)
+        X(f as f32, None)
+    }
+}
+
+impl From<&X> for X {
+    fn from(x: &X) -> X {
+        x.clone()
     }
 }
 
 impl<IX: Into<X>> std::ops::Mul<IX> for X {
     type Output = X;
     fn mul(self, other: IX) -> Self::Output {
-        X(self.0 * other.into().0)
+        let other = other.into();
+        X::combine(&self, "*", &other, self.0 * other.0)
     }
 }
 
 impl<IX: Into<X>> std::ops::Div<IX> for X {
     type Output = X;
     fn div(self, other: IX) -> Self::Output {
-        X(self.0 / other.into().0)
+        let other = other.into();
+        X::combine(&self, "/", &other, self.0 / other.0)
     }
 }
 
 impl<IX: Into<X>> std::ops::Add<IX> for X {
     type Output = X;
     fn add(self, other: IX) -> Self::Output {
-        X(self.0 + other.into().0)
+        let other = other.into();
+        X::combine(&self, "+", &other, self.0 + other.0)
     }
 }
 
 impl<IX: Into<X>> std::ops::Sub<IX> for X {
     type Output = X;
     fn sub(self, other: IX) -> Self::Output {
-        X(self.0 - other.into().0)
+        let other = other.into();
+        X::combine(&self, "-", &other, self.0 - other.0)
+    }
+}
+
+/// Arithmetic on borrowed numbers, to reuse an `X` without cloning it.
+impl<IX: Into<X>> std::ops::Mul<IX> for &X {
+    type Output = X;
+    fn mul(self, other: IX) -> Self::Output {
+        let other = other.into();
+        X::combine(self, "*", &other, self.0 * other.0)
+    }
+}
+
+impl<IX: Into<X>> std::ops::Div<IX> for &X {
+    type Output = X;
+    fn div(self, other: IX) -> Self::Output {
+        let other = other.into();
+        X::combine(self, "/", &other, self.0 / other.0)
+    }
+}
+
+impl<IX: Into<X>> std::ops::Add<IX> for &X {
+    type Output = X;
+    fn add(self, other: IX) -> Self::Output {
+        let other = other.into();
+        X::combine(self, "+", &other, self.0 + other.0)
+    }
+}
+
+impl<IX: Into<X>> std::ops::Sub<IX> for &X {
+    type Output = X;
+    fn sub(self, other: IX) -> Self::Output {
+        let other = other.into();
+        X::combine(self, "-", &other, self.0 - other.0)
     }
 }
 
 impl std::ops::Mul<X> for f32 {
     type Output = X;
     fn mul(self, other: X) -> X {
-        X(self * other.0)
+        X::from(self) * other
     }
 }
 
 impl std::ops::Mul<X> for i32 {
     type Output = X;
     fn mul(self, other: X) -> X {
-        X((self as f32) * other.0)
+        X::from(self) * other
     }
 }
 
 impl std::ops::Div<X> for f32 {
     type Output = X;
     fn div(self, other: X) -> X {
-        X(self / other.0)
+        X::from(self) / other
     }
 }
 
 impl std::ops::Div<X> for i32 {
     type Output = X;
     fn div(self, other: X) -> X {
-        X((self as f32) / other.0)
+        X::from(self) / other
     }
 }
 
 impl std::ops::Sub<X> for f32 {
     type Output = X;
     fn sub(self, other: X) -> X {
-        X(self - other.0)
+        X::from(self) - other
     }
 }
 
 impl std::ops::Sub<X> for i32 {
     type Output = X;
     fn sub(self, other: X) -> X {
-        X((self as f32) - other.0)
+        X::from(self) - other
     }
 }
 
 impl std::ops::Add<X> for f32 {
     type Output = X;
     fn add(self, other: X) -> X {
-        X(self + other.0)
+        X::from(self) + other
     }
 }
 
 impl std::ops::Add<X> for i32 {
     type Output = X;
     fn add(self, other: X) -> X {
-        X((self as f32) + other.0)
+        X::from(self) + other
     }
 }
 
 
-#[derive(Debug, Clone, Copy, PartialEq, Add, Neg)]
+#[derive(Debug, Clone, Copy, Add, Neg)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct XY(pub f32, pub f32); 
 
+impl XY {
+    /// Euclidean length of the vector.
+    pub fn norm(&self) -> f32 {
+        self.0.hypot(self.1)
+    }
+
+    pub fn dot(&self, other: XY) -> f32 {
+        self.0 * other.0 + self.1 * other.1
+    }
+
+    /// Rotate counterclockwise about the origin by `theta` degrees, like OpenSCAD `rotate`.
+    pub fn rotate(self, theta: f32) -> XY {
+        let (sin, cos) = theta.to_radians().sin_cos();
+        XY(self.0 * cos - self.1 * sin, self.0 * sin + self.1 * cos)
+    }
+}
+
 impl std::fmt::Display for XY {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", format!("[{}, {}]", &self.0, &self.1))
@@ -238,10 +354,55 @@ impl Mul for XY {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Add)]
+#[derive(Debug, Clone, Copy, Add, Neg)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 // pub struct XYZ(pub na::Vector3<X>);  // TODO: Remove pub na::
 pub struct XYZ(pub f32, pub f32, pub f32);
 
+impl XYZ {
+    /// Euclidean length of the vector.
+    pub fn norm(&self) -> f32 {
+        (self.0*self.0 + self.1*self.1 + self.2*self.2).sqrt()
+    }
+
+    pub fn dot(&self, other: XYZ) -> f32 {
+        self.0 * other.0 + self.1 * other.1 + self.2 * other.2
+    }
+
+    pub fn cross(&self, other: XYZ) -> XYZ {
+        XYZ(self.1 * other.2 - self.2 * other.1,
+            self.2 * other.0 - self.0 * other.2,
+            self.0 * other.1 - self.1 * other.0)
+    }
+
+    /// Angles for `rotate` that turn the Z axis to point along `self`.
+    pub fn rotation_from_z(&self) -> XYZ {
+        let theta = (self.2 / self.norm()).clamp(-1., 1.).acos().to_degrees();
+        let phi = self.1.atan2(self.0).to_degrees();
+        XYZ(0., theta, phi)
+    }
+
+    /// Rotate by the angles in degrees about the X, then Y, then Z axis, like OpenSCAD `rotate`.
+    pub fn rotate(self, angles: XYZ) -> XYZ {
+        let (sx, cx) = angles.0.to_radians().sin_cos();
+        let (sy, cy) = angles.1.to_radians().sin_cos();
+        let (sz, cz) = angles.2.to_radians().sin_cos();
+        let XYZ(x, y, z) = self;
+        let (y, z) = (y * cx - z * sx, y * sx + z * cx);
+        let (x, z) = (x * cy + z * sy, -x * sy + z * cy);
+        let (x, y) = (x * cz - y * sz, x * sz + y * cz);
+        XYZ(x, y, z)
+    }
+}
+
+impl Sub for XYZ {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0, self.1 - other.1, self.2 - other.2)
+    }
+}
+
 impl std::fmt::Display for XYZ {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         // write!(f, "{}", format!("{:?}", &self.0).replace(r"[[", r"[").replace("]]", "]"))
@@ -281,15 +442,15 @@ mod test {
 
     #[test]
     fn test_from_i32() {
-        assert_eq!(X::from(5_i32), X(5.));
-        assert_eq!(X::from(i32::MAX), X(2147483600.0));
+        assert_eq!(X::from(5_i32), X(5., None));
+        assert_eq!(X::from(i32::MAX), X(2147483600.0, None));
     }
 
     #[test]
     fn test_into_real() {
-        assert_eq!(<i32 as Into<X>>::into(5), X(5.));
-        assert_eq!(<u32 as Into<X>>::into(5), X(5.));
-        assert_eq!(<f64 as Into<X>>::into(5.0), X(5.));
+        assert_eq!(<i32 as Into<X>>::into(5), X(5., None));
+        assert_eq!(<u32 as Into<X>>::into(5), X(5., None));
+        assert_eq!(<f64 as Into<X>>::into(5.0), X(5., None));
     }
 
     #[test]
@@ -299,10 +460,19 @@ mod test {
 
     #[test]
     fn test_real_mul() {
-        assert_eq!(X(5.) * 2., X(10.));
-        assert_eq!(X(5.) * 2, X(10.));
-        assert_eq!(2. * X(5.), X(10.));
-        assert_eq!(2 * X(5.), X(10.));
+        assert_eq!(X(5., None) * 2., X(10., None));
+        assert_eq!(X(5., None) * 2, X(10., None));
+        assert_eq!(2. * X(5., None), X(10., None));
+        assert_eq!(2 * X(5., None), X(10., None));
+    }
+
+    #[test]
+    fn test_borrowed_expr() {
+        let w = crate::Param::new("w", 2).x();
+        assert_eq!(format!("{}", &w * 2 + &w), "(w * 2) + w");
+        assert_eq!(format!("{}", -&w), "-w");
+        assert_eq!(w.expr(), Some("w"));
+        assert_eq!(X::new(5.).expr(), None);
     }
 
     #[test]
@@ -324,6 +494,14 @@ mod test {
         assert_eq!(format!("{}", 8. * v3(1.,2., 4)), "[8, 16, 32]");
     }
 
+    #[test]
+    fn test_rotate() {
+        let XY(x, y) = v2(1, 0).rotate(90.);
+        assert!(x.abs() < 1e-6 && (y - 1.).abs() < 1e-6);
+        let XYZ(x, y, z) = v3(0, 0, 1).rotate(v3(90, 0, 90));
+        assert!((x - 1.).abs() < 1e-6 && y.abs() < 1e-6 && z.abs() < 1e-6);
+    }
+
     #[test]
     fn test_into_real2() {
         assert_eq!(XY::from( (5_i32, 10_i32) ), v2(5., 10.));
@@ -0,0 +1,110 @@
+//! Command line handling shared by the model binaries.
+
+use crate::*;
+use anyhow::{anyhow, bail, Result};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "options:
+  --param NAME=VALUE     set a parameter, may be repeated
+  --params FILE.json     set parameters from a JSON file or OpenSCAD parameter set file
+  --param-set NAME       parameter set to use from an OpenSCAD parameter set file
+  --list-params          list the parameters and exit
+  --out FILE.scad        write the SCAD to FILE instead of standard output";
+
+/// Options of a model binary after parsing its command line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cli {
+    pub params: Params,
+    pub out: Option<PathBuf>,
+    pub list_params: bool,
+}
+
+impl Cli {
+    /// Parse `args`, without the program name, against the default `params` of a model.
+    /// JSON files are applied before `--param` settings.
+    pub fn parse<I: IntoIterator<Item = String>>(params: Params, args: I) -> Result<Cli> {
+        let mut cli = Cli { params, out: None, list_params: false };
+        let (mut assignments, mut files, mut set) = (vec![], vec![], None);
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value\n{}", arg, USAGE));
+            match arg.as_str() {
+                "--param" => assignments.push(value()?),
+                "--params" => files.push(value()?),
+                "--param-set" => set = Some(value()?),
+                "--out" => cli.out = Some(PathBuf::from(value()?)),
+                "--list-params" => cli.list_params = true,
+                _ => bail!("unknown option {}\n{}", arg, USAGE),
+            }
+        }
+        for file in files {
+            cli.params.set_json(&std::fs::read_to_string(&file)?, set.as_deref())
+                .map_err(|e| anyhow!("{}: {}", file, e))?;
+        }
+        for assignment in assignments {
+            cli.params.set_str(&assignment)?;
+        }
+        Ok(cli)
+    }
+
+    /// The Customizer declarations followed by the model.
+    pub fn scad(&self, shape: &D3) -> String {
+        let declarations = self.params.scad();
+        if declarations.is_empty() {
+            shape.scad()
+        } else {
+            format!("{}\n\n{}", declarations, shape)
+        }
+    }
+
+    /// The SCAD of `build` with the parameters, declaring only those the model is written in terms of.
+    /// The others are listed in a leading comment, since the Customizer cannot change them.
+    pub fn build<F: Fn(&Params) -> D3>(&self, build: F) -> String {
+        let params = self.params.clone().bake(&build);
+        let shape = build(&params);
+        let baked = params.params.iter().zip(&self.params.params)
+            .filter(|(p, q)| q.customizer && !p.customizer)
+            .map(|(p, _)| format!("{} = {}", p.name, p.value))
+            .collect::<Vec<_>>();
+        let scad = Cli { params, ..self.clone() }.scad(&shape);
+        if baked.is_empty() {
+            scad
+        } else {
+            format!("// Not customizable, baked into vectors: {}\n{}", baked.join(", "), scad)
+        }
+    }
+}
+
+/// Main of a model binary: parse the command line, then list the parameters
+/// or write the SCAD of `build` with the chosen parameters.
+pub fn run_model<F: Fn(&Params) -> D3>(params: Params, build: F) -> Result<()> {
+    let cli = Cli::parse(params, std::env::args().skip(1))?;
+    if cli.list_params {
+        println!("{}", cli.params.list());
+        return Ok(());
+    }
+    let scad = cli.build(build);
+    match &cli.out {
+        Some(path) => std::fs::write(path, scad + "\n")?,
+        None => println!("{}", scad),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let params = Params::new().with(Param::new("gap", 0.1));
+        let args = ["--param", "gap=0.15", "--out", "cube.scad"].map(String::from);
+        let cli = Cli::parse(params.clone(), args).unwrap();
+        assert_eq!(cli.params.value("gap"), 0.15);
+        assert_eq!(cli.out, Some(PathBuf::from("cube.scad")));
+        assert_eq!(cli.scad(&D3::cube(cli.params.x("gap"))), "gap = 0.15;\n\ncube(size = gap);");
+        assert_eq!(cli.build(|p| D3::cube(1).translate(v3(p.x("gap"), 0, 0))), "// Not customizable, baked into vectors: gap = 0.15\ntranslate(v = [0.15, 0, 0]) {\n  cube(size = 1);\n}");
+        assert!(Cli::parse(params.clone(), ["--out"].map(String::from)).is_err());
+        assert!(Cli::parse(params, ["--bogus"].map(String::from)).is_err());
+    }
+}
@@ -16,7 +16,8 @@ pub trait DIterator<T> : Iterator<Item=T> {
     fn minkowski(self: Self) -> T where Self: Iterator<Item = T>;
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ColorEnum {
     Blue,
     Green,
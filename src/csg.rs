@@ -0,0 +1,143 @@
+//! Boolean operations shared by `D2` and `D3`.
+
+use crate::*;
+
+/// OpenSCAD operation joining any number of children.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoolOp {
+    Union,
+    Hull,
+    Intersection,
+    Minkowski,
+}
+
+impl BoolOp {
+    /// Name of the OpenSCAD module.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoolOp::Union => "union",
+            BoolOp::Hull => "hull",
+            BoolOp::Intersection => "intersection",
+            BoolOp::Minkowski => "minkowski",
+        }
+    }
+}
+
+/// Shapes joined by `BoolOp`s. Both dimensions build, flatten and emit joins through this trait.
+pub trait Csg: Sized + Clone + SCAD {
+    fn join(op: BoolOp, v: Vec<Self>) -> Self;
+
+    /// The operation and children if `self` is a join.
+    fn as_join(&self) -> Option<(BoolOp, &Vec<Self>)>;
+
+    /// The operation and children if `self` is a join, otherwise `self` back.
+    fn into_join(self) -> Result<(BoolOp, Vec<Self>), Self>;
+
+    /// Join `self` and `other` by `op`, appending to `self` if it already is a join by `op`.
+    fn combine(self, op: BoolOp, other: Self) -> Self {
+        match self.into_join() {
+            Ok((inner, mut v)) if inner == op => {
+                v.push(other);
+                Self::join(op, v)
+            },
+            Ok((inner, v)) => Self::join(op, vec![Self::join(inner, v), other]),
+            Err(shape) => Self::join(op, vec![shape, other]),
+        }
+    }
+
+    /// Hull of `self`, taking the children of a union directly.
+    fn hull_of(self) -> Self {
+        match self.into_join() {
+            Ok((BoolOp::Union, v)) => Self::join(BoolOp::Hull, v),
+            Ok((op, v)) => Self::join(BoolOp::Hull, vec![Self::join(op, v)]),
+            Err(shape) => Self::join(BoolOp::Hull, vec![shape]),
+        }
+    }
+
+    /// True for a union without children, which has no geometry.
+    fn is_nothing(&self) -> bool {
+        matches!(self.as_join(), Some((BoolOp::Union, v)) if v.is_empty())
+    }
+}
+
+/// SCAD of `op` applied to `children`.
+pub fn scad_join<T: SCAD>(op: BoolOp, children: &[T]) -> String {
+    format!("{}() {{\n  {}\n}}", op.name(),
+        children.iter().map(|x| x.indent()).collect::<Vec<_>>().join("\n  "))
+}
+
+impl<S: Csg, T: Iterator<Item=S>> DIterator<S> for T {
+    fn hull(self) -> S {
+        S::join(BoolOp::Hull, self.collect())
+    }
+
+    fn union(self) -> S {
+        S::join(BoolOp::Union, self.collect())
+    }
+
+    fn intersection(self) -> S {
+        S::join(BoolOp::Intersection, self.collect())
+    }
+
+    fn minkowski(self) -> S {
+        S::join(BoolOp::Minkowski, self.collect())
+    }
+}
+
+impl Csg for D2 {
+    fn join(op: BoolOp, v: Vec<D2>) -> D2 {
+        D2::Join(op, Box::new(v))
+    }
+
+    fn as_join(&self) -> Option<(BoolOp, &Vec<D2>)> {
+        match self {
+            D2::Join(op, v) => Some((*op, v)),
+            _ => None,
+        }
+    }
+
+    fn into_join(self) -> Result<(BoolOp, Vec<D2>), D2> {
+        match self {
+            D2::Join(op, v) => Ok((op, *v)),
+            shape => Err(shape),
+        }
+    }
+}
+
+impl Csg for D3 {
+    fn join(op: BoolOp, v: Vec<D3>) -> D3 {
+        D3::Join(op, Box::new(v))
+    }
+
+    fn as_join(&self) -> Option<(BoolOp, &Vec<D3>)> {
+        match self {
+            D3::Join(op, v) => Some((*op, v)),
+            _ => None,
+        }
+    }
+
+    fn into_join(self) -> Result<(BoolOp, Vec<D3>), D3> {
+        match self {
+            D3::Join(op, v) => Ok((op, *v)),
+            shape => Err(shape),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_same_rules() {
+        let d2 = D2::circle(1).minkowski(D2::square(1)).minkowski(D2::circle(2));
+        let d3 = D3::sphere(1).minkowski(D3::cube(1)).minkowski(D3::sphere(2));
+        assert_eq!(d2.as_join().map(|(op, v)| (op, v.len())), Some((BoolOp::Minkowski, 3)));
+        assert_eq!(d3.as_join().map(|(op, v)| (op, v.len())), Some((BoolOp::Minkowski, 3)));
+        assert_eq!(D3::cube(1).add(D3::cube(2)).intersection(D3::cube(3)).scad(),
+            "intersection() {\n  union() {\n    cube(size = 1);\n    cube(size = 2);\n  }\n  cube(size = 3);\n}");
+        assert_eq!(D3::cube(1).translate(v3(1, 0, 0)).translate(v3(0, 1, 0)).scad(),
+            "translate(v = [1, 1, 0]) {\n  cube(size = 1);\n}");
+    }
+}
@@ -0,0 +1,217 @@
+//! Canonical equality and hashing of geometry trees for dedup and caching.
+//!
+//! Floats compare and hash by their bits after normalization: `-0.0` is `0.0` and every NaN is one
+//! NaN, so equality is reflexive and values that compare equal always hash equal. An `X` with a
+//! SCAD expression differs from the plain number of the same value, since they emit different SCAD.
+
+use crate::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Bits of `value` with the sign of zero and the NaN payload normalized.
+pub fn canonical_bits(value: f32) -> u32 {
+    if value == 0. {
+        0
+    } else if value.is_nan() {
+        f32::NAN.to_bits()
+    } else {
+        value.to_bits()
+    }
+}
+
+fn hash_f32<H: Hasher>(value: f32, state: &mut H) {
+    canonical_bits(value).hash(state);
+}
+
+fn same(a: f32, b: f32) -> bool {
+    canonical_bits(a) == canonical_bits(b)
+}
+
+impl PartialEq for X {
+    fn eq(&self, other: &Self) -> bool {
+        same(self.0, other.0) && self.1 == other.1
+    }
+}
+
+impl PartialEq for XY {
+    fn eq(&self, other: &Self) -> bool {
+        same(self.0, other.0) && same(self.1, other.1)
+    }
+}
+
+impl PartialEq for XYZ {
+    fn eq(&self, other: &Self) -> bool {
+        same(self.0, other.0) && same(self.1, other.1) && same(self.2, other.2)
+    }
+}
+
+impl PartialEq for Keyframes {
+    fn eq(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+            && self.keys().len() == other.keys().len()
+            && self.keys().iter().zip(other.keys()).all(|((t, a), (u, b))| same(*t, *u) && a == b)
+    }
+}
+
+impl PartialEq for D3 {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (D3::Cube(a), D3::Cube(b)) | (D3::Sphere(a), D3::Sphere(b)) => a == b,
+            (D3::Cuboid(a), D3::Cuboid(b)) => a == b,
+            (D3::Color(c, a), D3::Color(d, b)) => c == d && a == b,
+            (D3::Cylinder(h, r), D3::Cylinder(g, s)) => h == g && r == s,
+            (D3::Polyhedron(p, f), D3::Polyhedron(q, g)) => f == g && p.len() == q.len()
+                && p.iter().flatten().zip(q.iter().flatten()).all(|(a, b)| same(*a, *b)),
+            (D3::Scale(x, a), D3::Scale(y, b)) => x == y && a == b,
+            (D3::Translate(u, a), D3::Translate(v, b)) | (D3::Scale3(u, a), D3::Scale3(v, b))
+                | (D3::Rotate(u, a), D3::Rotate(v, b)) | (D3::Mirror(u, a), D3::Mirror(v, b)) => u == v && a == b,
+            (D3::LinearExtrude(x, a), D3::LinearExtrude(y, b)) | (D3::RotateExtrude(x, a), D3::RotateExtrude(y, b)) =>
+                x == y && a == b,
+            (D3::Difference(a, b), D3::Difference(c, d)) => a == c && b == d,
+            (D3::Join(op, a), D3::Join(other_op, b)) => op == other_op && a == b,
+            (D3::Animate(k, a), D3::Animate(l, b)) => k == l && a == b,
+            (D3::Named(m, a), D3::Named(n, b)) => m == n && a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Hash for X {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_f32(self.0, state);
+        self.expr().hash(state);
+    }
+}
+
+impl Hash for XY {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_f32(self.0, state);
+        hash_f32(self.1, state);
+    }
+}
+
+impl Hash for XYZ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_f32(self.0, state);
+        hash_f32(self.1, state);
+        hash_f32(self.2, state);
+    }
+}
+
+impl Hash for Keyframes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        for (t, xyz) in self.keys() {
+            hash_f32(*t, state);
+            xyz.hash(state);
+        }
+    }
+}
+
+impl Hash for D2 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            D2::Circle(x) | D2::Square(x) => x.hash(state),
+            D2::Rectangle(xy) => xy.hash(state),
+            D2::Polygon(points) => points.hash(state),
+            D2::Color(color, shape) => (color, shape).hash(state),
+            D2::Rotate(x, shape) | D2::Scale(x, shape) => (x, shape).hash(state),
+            D2::Scale2(xy, shape) | D2::Translate(xy, shape) | D2::Mirror(xy, shape) => (xy, shape).hash(state),
+            D2::Join(op, shapes) => (op, shapes).hash(state),
+            D2::Difference(a, b) => (a, b).hash(state),
+        }
+    }
+}
+
+impl Hash for D3 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            D3::Cube(x) | D3::Sphere(x) => x.hash(state),
+            D3::Cuboid(xyz) => xyz.hash(state),
+            D3::Color(color, shape) => (color, shape).hash(state),
+            D3::Cylinder(h, r) => (h, r).hash(state),
+            D3::Polyhedron(points, faces) => {
+                for point in points.iter() {
+                    point.iter().for_each(|v| hash_f32(*v, state));
+                }
+                faces.hash(state);
+            },
+            D3::Scale(x, shape) => (x, shape).hash(state),
+            D3::Translate(xyz, shape) | D3::Scale3(xyz, shape) | D3::Rotate(xyz, shape) | D3::Mirror(xyz, shape) =>
+                (xyz, shape).hash(state),
+            D3::LinearExtrude(x, d2) | D3::RotateExtrude(x, d2) => (x, d2).hash(state),
+            D3::Difference(a, b) => (a, b).hash(state),
+            D3::Join(op, shapes) => (op, shapes).hash(state),
+            D3::Animate(keyframes, shape) => (keyframes, shape).hash(state),
+            D3::Named(meta, shape) => (meta, shape).hash(state),
+        }
+    }
+}
+
+impl Eq for X {}
+impl Eq for XY {}
+impl Eq for XYZ {}
+impl Eq for Keyframes {}
+impl Eq for D2 {}
+impl Eq for D3 {}
+
+impl D2 {
+    /// Canonical hash of the tree, stable within a build of the crate.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl D3 {
+    /// Canonical hash of the tree, stable within a build of the crate.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_eq() {
+        assert_eq!(D2::circle(5).translate(v2(1, 0)), D2::Translate(v2(1, 0), Box::new(D2::Circle(X(5., None)))));
+        assert_ne!(D3::cube(2).add(D3::sphere(1)), D3::sphere(1).add(D3::cube(2)));
+        let size = Param::new("size", 2);
+        assert_ne!(D3::cube(size.x()), D3::cube(2));
+        assert_ne!(D3::cube(size.x()).fingerprint(), D3::cube(2).fingerprint());
+        assert_eq!(D3::cube(size.x()).fingerprint(), D3::cube(size.clone().x()).fingerprint());
+        assert_eq!(D3::cube(size.numeric().x()), D3::cube(2));
+    }
+
+    #[test]
+    fn test_nan_eq() {
+        let nan = D3::cube(f32::NAN).translate(v3(0., -0., f32::NAN));
+        assert_eq!(nan, nan.clone());
+        assert_eq!(nan, D3::cube(-f32::NAN).translate(v3(0, 0, f32::NAN)));
+        let points = Box::new(vec![[0., 0., f32::NAN]]);
+        assert_eq!(D3::Polyhedron(points.clone(), Box::default()), D3::Polyhedron(points, Box::default()));
+        let set: HashSet<D3> = [nan.clone(), nan].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(D3::cube(2).translate(v3(0., -0., 1.)).fingerprint(), D3::cube(2).translate(v3(0, 0, 1)).fingerprint());
+        assert_ne!(D3::cube(2).fingerprint(), D3::sphere(2).fingerprint());
+        assert_eq!(canonical_bits(f32::NAN), canonical_bits(-f32::NAN));
+    }
+
+    #[test]
+    fn test_dedup() {
+        let parts: HashSet<D3> = (0..4).map(|ii| D3::cube(ii % 2 + 1).rotate(v3(0, 0, 90))).collect();
+        assert_eq!(parts.len(), 2);
+    }
+}
@@ -0,0 +1,142 @@
+//! Native convex hulls, evaluating `hull()` joins into explicit polygons and polyhedra.
+
+use crate::*;
+use anyhow::{anyhow, bail, Result};
+use qhull::Qh;
+use std::collections::HashMap;
+
+/// Convex hull of `points` as an outward triangle mesh, computed by qhull.
+pub fn convex_hull(points: &[XYZ]) -> Result<Mesh> {
+    if points.len() < 4 {
+        bail!("a hull needs at least 4 points, got {}", points.len());
+    }
+    let qh = Qh::builder()
+        .compute(true)
+        .triangulate(true)
+        .build_from_iter(points.iter().map(|p| [p.0 as f64, p.1 as f64, p.2 as f64]))
+        .map_err(|e| anyhow!("{}", e))?;
+    let mut index: HashMap<u32, usize> = HashMap::new();
+    let mut vertices: Vec<XYZ> = vec![];
+    let mut triangles = vec![];
+    for face in qh.simplices() {
+        let corners: Vec<usize> = face.vertices().ok_or_else(|| anyhow!("qhull face without vertices"))?
+            .iter()
+            .map(|vertex| *index.entry(vertex.id()).or_insert_with(|| {
+                let p = vertex.point();
+                vertices.push(XYZ(p[0] as f32, p[1] as f32, p[2] as f32));
+                vertices.len() - 1
+            }))
+            .collect();
+        let [a, b, c] = corners[..] else { bail!("qhull face with {} vertices", corners.len()) };
+        let n = face.normal();
+        let (pa, pb, pc) = (vertices[a], vertices[b], vertices[c]);
+        let outward = (pb - pa).cross(pc - pa).dot(XYZ(n[0] as f32, n[1] as f32, n[2] as f32)) >= 0.;
+        triangles.push(if outward { [a, b, c] } else { [a, c, b] });
+    }
+    Ok(Mesh { vertices, triangles })
+}
+
+impl D3 {
+    /// Points whose convex hull is the hull of the shape, tessellating curves with `segments`.
+    /// `None` for differences and intersections, whose hull needs a CSG evaluation.
+    pub fn hull_points(&self, segments: u32) -> Option<Vec<XYZ>> {
+        let map = |shape: &D3, f: &dyn Fn(XYZ) -> XYZ| shape.hull_points(segments).map(|v| v.into_iter().map(f).collect());
+        match self {
+            D3::Join(BoolOp::Union, v) | D3::Join(BoolOp::Hull, v) => v.iter()
+                .map(|x| x.hull_points(segments))
+                .collect::<Option<Vec<_>>>()
+                .map(|v| v.concat()),
+            D3::Join(BoolOp::Minkowski, v) => v.iter().map(|x| x.hull_points(segments))
+                .reduce(|a, b| {
+                    let (a, b) = (a?, b?);
+                    Some(a.iter().flat_map(|p| b.iter().map(move |q| *p + *q)).collect())
+                })
+                .unwrap_or(Some(vec![])),
+            D3::LinearExtrude(X(h, _), profile) => Some(profile.regions().iter()
+                .flat_map(|region| region.outline.iter().flat_map(|p| [XYZ(p.0, p.1, 0.), XYZ(p.0, p.1, *h)]))
+                .collect()),
+            D3::RotateExtrude(X(angle, _), profile) => {
+                let steps = segments.max(3) as usize;
+                Some(profile.regions().iter().flat_map(|region| region.outline.clone()).flat_map(|p| {
+                    (0..=steps).map(move |ii| {
+                        let (sin, cos) = (angle * ii as f32 / steps as f32).to_radians().sin_cos();
+                        XYZ(p.0 * cos, p.0 * sin, p.1)
+                    })
+                }).collect())
+            },
+            D3::Color(_, shape) | D3::Named(_, shape) => shape.hull_points(segments),
+            D3::Translate(xyz, shape) => map(shape, &|p| p + *xyz),
+            D3::Rotate(xyz, shape) => map(shape, &|p| p.rotate(*xyz)),
+            D3::Mirror(n, shape) => map(shape, &|p| p - *n * (2. * p.dot(*n) / n.dot(*n))),
+            D3::Scale(X(s, _), shape) => map(shape, &|p| p * *s),
+            D3::Scale3(XYZ(x, y, z), shape) => map(shape, &|p| XYZ(p.0 * x, p.1 * y, p.2 * z)),
+            shape => shape.mesh(segments).map(|m| m.vertices),
+        }
+    }
+
+    /// The convex hull of the shape as a polyhedron.
+    pub fn hull_polyhedron(&self) -> Result<D3> {
+        let points = self.hull_points(SEGMENTS).ok_or_else(|| anyhow!("cannot gather the points of a hull"))?;
+        Ok(convex_hull(&points)?.polyhedron())
+    }
+
+    /// Replace every `hull()` join, in 3D and in extruded profiles, by its evaluated hull.
+    /// Hulls that cannot be evaluated are left for OpenSCAD.
+    pub fn eval_hulls(self) -> D3 {
+        self.map_d2_nodes(&mut |shape: D2| shape.eval_hull())
+            .map_nodes(&mut |shape| match shape {
+                D3::Join(BoolOp::Hull, _) => shape.hull_polyhedron().unwrap_or(shape),
+                shape => shape,
+            })
+    }
+}
+
+impl D2 {
+    fn eval_hull(self) -> D2 {
+        match self {
+            D2::Join(BoolOp::Hull, _) => match &self.regions()[..] {
+                [region] => D2::Polygon(Box::new(region.outline.clone())),
+                _ => self,
+            },
+            shape => shape,
+        }
+    }
+
+    /// Replace every `hull()` join by the polygon of its evaluated hull.
+    pub fn eval_hulls(self) -> D2 {
+        self.map_nodes(&mut |shape: D2| shape.eval_hull())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_convex_hull() {
+        let mut points: Vec<XYZ> = Mesh::cuboid(v3(2, 2, 2)).vertices;
+        points.push(v3(1, 1, 1));
+        let hull = convex_hull(&points).unwrap();
+        assert_eq!(hull.triangles.len(), 12);
+        assert!((hull.volume() - 8.).abs() < 1e-4);
+        assert!(convex_hull(&points[..3]).is_err());
+    }
+
+    #[test]
+    fn test_eval_hulls() {
+        let shape = D3::cube(2).add(D3::cube(2).translate(v3(4, 0, 0))).hull().eval_hulls();
+        assert!(matches!(shape, D3::Polyhedron(_, _)));
+        assert!((shape.volume().unwrap() - 24.).abs() < 1e-3, "{:?}", shape.volume());
+        assert_eq!(shape.bounds(), D3::cuboid(v3(6, 2, 2)).bounds());
+        let left = D3::cube(1).difference(D3::sphere(1)).add(D3::cube(1)).hull();
+        assert_eq!(left.clone().eval_hulls(), left);
+    }
+
+    #[test]
+    fn test_eval_d2_hulls() {
+        let profile = D2::square(1).add(D2::square(1).translate(v2(3, 0))).hull();
+        let extruded = profile.clone().linear_extrude(2).eval_hulls();
+        assert_eq!(extruded.scad(), "linear_extrude(height = 2) {\n  polygon(points = [ [4, 0], [4, 1], [0, 1], [0, 0] ]);\n}");
+        assert!((profile.eval_hulls().area().unwrap() - 4.).abs() < 1e-5);
+    }
+}
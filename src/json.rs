@@ -0,0 +1,100 @@
+//! JSON form of `D2` and `D3` trees, wrapped in a document carrying the format version.
+
+use crate::*;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Version written to every document. Bump it when the JSON layout of a shape changes.
+pub const JSON_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Document<T> {
+    version: u32,
+    shape: T,
+}
+
+/// `X` as a plain number, or as `{"value", "expr"}` when it carries a SCAD expression.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum XRepr {
+    Value(f32),
+    Expr { value: f32, expr: String },
+}
+
+impl Serialize for X {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.expr() {
+            None => XRepr::Value(self.0),
+            Some(expr) => XRepr::Expr { value: self.0, expr: expr.to_string() },
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for X {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<X, D::Error> {
+        Ok(match XRepr::deserialize(deserializer)? {
+            XRepr::Value(value) => X(value, None),
+            XRepr::Expr { value, expr } => X(value, Some(expr.into())),
+        })
+    }
+}
+
+fn to_json<T: Serialize>(shape: &T) -> String {
+    serde_json::to_string(&Document { version: JSON_VERSION, shape }).expect("shapes always serialize")
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(json: &str) -> Result<T> {
+    let doc: Document<T> = serde_json::from_str(json)?;
+    if doc.version != JSON_VERSION {
+        bail!("unsupported JSON version {}, expected {}", doc.version, JSON_VERSION);
+    }
+    Ok(doc.shape)
+}
+
+impl D2 {
+    pub fn to_json(&self) -> String {
+        to_json(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<D2> {
+        from_json(json)
+    }
+}
+
+impl D3 {
+    pub fn to_json(&self) -> String {
+        to_json(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<D3> {
+        from_json(json)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_d2_json() {
+        let shape = D2::circle(5).translate(v2(1., 2.)).add(D2::square(2));
+        let json = shape.to_json();
+        assert_eq!(json, r#"{"version":1,"shape":{"Join":["Union",[{"Translate":[[1.0,2.0],{"Circle":5.0}]},{"Square":2.0}]]}}"#);
+        assert_eq!(D2::from_json(&json).unwrap().scad(), shape.scad());
+    }
+
+    #[test]
+    fn test_d3_json_round_trip() {
+        let height = Param::new("height", 3).x();
+        let shape = D3::cube(2).add(D3::cylinder(1, height * 2)).hull().color(ColorEnum::Red);
+        let back = D3::from_json(&shape.to_json()).unwrap();
+        assert_eq!(back.scad(), shape.scad());
+        assert!(back.scad().contains("height * 2"));
+    }
+
+    #[test]
+    fn test_json_version() {
+        assert!(D3::from_json(r#"{"version":0,"shape":{"Cube":1.0}}"#).is_err());
+        assert!(D3::from_json(r#"{"version":1,"shape":{"Cube":1.0}}"#).is_ok());
+    }
+}
@@ -0,0 +1,192 @@
+//! Regular arrangements of shapes: 3D lattices for `D3` and 2D tilings for `D2`.
+
+use crate::*;
+
+/// A 3D lattice given by a rectangular cell `(a, b, c)` and the points of the cell.
+#[derive(Clone, Debug)]
+pub struct Lattice {
+    pub cell: XYZ,
+    pub basis: Vec<XYZ>,
+}
+
+impl Lattice {
+    /// Simple cubic lattice with spacing `a`.
+    pub fn cubic<T: Into<X>>(a: T) -> Lattice {
+        let a = a.into().0;
+        Lattice { cell: v3(a, a, a), basis: vec![v3(0, 0, 0)] }
+    }
+
+    /// Body-centered cubic lattice with conventional cube edge `a`.
+    /// Truncated octahedra with square faces `a/2` from their center tile space on this lattice.
+    pub fn bcc<T: Into<X>>(a: T) -> Lattice {
+        let a = a.into().0;
+        Lattice {
+            cell: v3(a, a, a),
+            basis: vec![v3(0, 0, 0), v3(a/2., a/2., a/2.)],
+        }
+    }
+
+    /// Face-centered cubic lattice with conventional cube edge `a`.
+    pub fn fcc<T: Into<X>>(a: T) -> Lattice {
+        let a = a.into().0;
+        Lattice {
+            cell: v3(a, a, a),
+            basis: vec![v3(0, 0, 0), v3(a/2., a/2., 0), v3(a/2., 0, a/2.), v3(0, a/2., a/2.)],
+        }
+    }
+
+    /// Hexagonal close packed lattice with nearest neighbor distance `a`.
+    /// Uses the rectangular cell `a x a*sqrt(3) x c` so regions are boxes.
+    pub fn hcp<T: Into<X>>(a: T) -> Lattice {
+        let a = a.into().0;
+        let h = a * 3.0_f32.sqrt();  // width of two rows of spheres
+        let c = a * (8.0_f32/3.).sqrt();  // height of two layers of spheres
+        Lattice {
+            cell: v3(a, h, c),
+            basis: vec![v3(0, 0, 0), v3(a/2., h/2., 0), v3(a/2., h/6., c/2.), v3(0, h*2./3., c/2.)],
+        }
+    }
+
+    /// Position of point `n` of the basis in cell `(i, j, k)`.
+    pub fn point(&self, ijk: (i32, i32, i32), n: usize) -> XYZ {
+        let XYZ(a, b, c) = self.cell;
+        v3(a * ijk.0 as f32, b * ijk.1 as f32, c * ijk.2 as f32) + self.basis[n]
+    }
+
+    /// All lattice points for cells with `min <= (i, j, k) < max`.
+    pub fn points_range(&self, min: (i32, i32, i32), max: (i32, i32, i32)) -> Vec<XYZ> {
+        (min.2..max.2)
+            .flat_map(|k| (min.1..max.1).flat_map(move |j| (min.0..max.0).map(move |i| (i, j, k))))
+            .flat_map(|ijk| (0..self.basis.len()).map(move |n| self.point(ijk, n)))
+            .collect()
+    }
+
+    /// All lattice points in a block of `xyz_dim` cells starting at the origin.
+    pub fn points(&self, xyz_dim: (u32, u32, u32)) -> Vec<XYZ> {
+        self.points_range((0, 0, 0), (xyz_dim.0 as i32, xyz_dim.1 as i32, xyz_dim.2 as i32))
+    }
+}
+
+/// A 2D tiling given by a rectangular cell and the placement `(offset, rotation)` of each tile in the cell.
+#[derive(Clone, Debug)]
+pub struct Tiling {
+    pub cell: XY,
+    pub motif: Vec<(XY, X)>,
+}
+
+impl Tiling {
+    /// Square tiling with spacing `a`.
+    pub fn square<T: Into<X>>(a: T) -> Tiling {
+        let a = a.into().0;
+        Tiling { cell: v2(a, a), motif: vec![(v2(0, 0), X(0., None))] }
+    }
+
+    /// Hexagonal tiling with distance `w` between the centers of neighboring tiles.
+    /// Rows alternate by `w/2` so regions are rectangular.
+    pub fn hex<T: Into<X>>(w: T) -> Tiling {
+        let w = w.into().0;
+        let h = w * 3.0_f32.sqrt();
+        Tiling { cell: v2(w, h), motif: vec![(v2(0, 0), X(0., None)), (v2(w/2., h/2.), X(0., None))] }
+    }
+
+    /// Triangular tiling with edge length `s`.
+    /// Tiles are placed by their centroid, every other tile is rotated by 180 degrees.
+    pub fn triangular<T: Into<X>>(s: T) -> Tiling {
+        let s = s.into().0;
+        let h = s * 3.0_f32.sqrt() / 2.;  // height of one row of triangles
+        Tiling {
+            cell: v2(s, 2.*h),
+            motif: vec![
+                (v2(s/2., h/3.), X(0., None)),
+                (v2(s, h*2./3.), X(180., None)),
+                (v2(s, h*4./3.), X(0., None)),
+                (v2(s/2., h*5./3.), X(180., None)),
+            ],
+        }
+    }
+
+    /// Placement of tile `n` of the motif in cell `(i, j)`.
+    pub fn placement(&self, ij: (i32, i32), n: usize) -> (XY, X) {
+        let (offset, theta) = self.motif[n].clone();
+        (v2(self.cell.0 * ij.0 as f32, self.cell.1 * ij.1 as f32) + offset, theta)
+    }
+
+    /// All tile placements for cells with `min <= (i, j) < max`.
+    pub fn placements_range(&self, min: (i32, i32), max: (i32, i32)) -> Vec<(XY, X)> {
+        (min.1..max.1)
+            .flat_map(|j| (min.0..max.0).map(move |i| (i, j)))
+            .flat_map(|ij| (0..self.motif.len()).map(move |n| self.placement(ij, n)))
+            .collect()
+    }
+
+    /// All tile placements in a block of `xy_dim` cells starting at the origin.
+    pub fn placements(&self, xy_dim: (u32, u32)) -> Vec<(XY, X)> {
+        self.placements_range((0, 0), (xy_dim.0 as i32, xy_dim.1 as i32))
+    }
+}
+
+impl D3 {
+    /// Copies of `self` translated to every point of `lattice` in a block of `xyz_dim` cells.
+    /// The copy at the origin is left in place.
+    pub fn iter_lattice(self, lattice: &Lattice, xyz_dim: (u32, u32, u32)) -> impl Iterator<Item = D3> {
+        lattice.points(xyz_dim).into_iter().map(move |xyz|
+            if xyz == v3(0, 0, 0) { self.clone() } else { self.clone().translate(xyz) }
+            )
+    }
+}
+
+impl D2 {
+    /// Copies of `self` placed on every tile of `tiling` in a block of `xy_dim` cells.
+    /// `self` should be centered at the origin since rotated tiles turn about it.
+    /// Tiles at the origin are not translated.
+    pub fn iter_tiling(self, tiling: &Tiling, xy_dim: (u32, u32)) -> impl Iterator<Item = D2> {
+        tiling.placements(xy_dim).into_iter().map(move |(xy, theta)| {
+            let tile = if theta.0 == 0. { self.clone() } else { self.clone().rotate(theta) };
+            if xy == v2(0, 0) { tile } else { tile.translate(xy) }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bcc_points() {
+        assert_eq!(Lattice::bcc(2).points((2, 1, 1)),
+            vec![v3(0, 0, 0), v3(1, 1, 1), v3(2, 0, 0), v3(3, 1, 1)]);
+    }
+
+    #[test]
+    fn test_fcc_points() {
+        assert_eq!(Lattice::fcc(2).points_range((-1, 0, 0), (0, 1, 1)),
+            vec![v3(-2, 0, 0), v3(-1, 1, 0), v3(-1, 0, 1), v3(-2, 1, 1)]);
+    }
+
+    #[test]
+    fn test_hcp_neighbors() {
+        let points = Lattice::hcp(1).points((2, 2, 2));
+        let dist = |p: &XYZ, q: &XYZ| ((p.0-q.0).powi(2) + (p.1-q.1).powi(2) + (p.2-q.2).powi(2)).sqrt();
+        assert!((dist(&points[0], &points[2]) - 1.).abs() < 1e-5);
+        for (ii, p) in points.iter().enumerate() {
+            for q in &points[ii+1..] {
+                assert!(dist(p, q) > 1. - 1e-5, "{:?} {:?}", p, q);
+            }
+        }
+    }
+
+    #[test]
+    fn test_iter_lattice() {
+        assert_eq!(D3::cube(1).iter_lattice(&Lattice::cubic(2), (2, 1, 1)).union().scad(),
+            "union() {\n  cube(size = 1);\n  translate(v = [2, 0, 0]) {\n    cube(size = 1);\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn test_iter_tiling() {
+        assert_eq!(D2::circle(1).iter_tiling(&Tiling::square(3), (2, 1)).union().scad(),
+            "union() {\n  circle(d = 1);\n  translate(v = [3, 0]) {\n    circle(d = 1);\n  }\n}"
+        );
+        assert_eq!(Tiling::triangular(2).placements((1, 1)).iter().filter(|(_, theta)| theta.0 == 180.).count(), 2);
+    }
+}
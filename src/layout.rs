@@ -0,0 +1,77 @@
+//! Arranging parts on the print bed.
+
+use crate::*;
+use anyhow::{bail, Result};
+
+/// A print bed of size `bed` with at least `spacing` between parts.
+#[derive(Clone, Debug)]
+pub struct Layout {
+    pub bed: XY,
+    pub spacing: X,
+}
+
+impl Layout {
+    pub fn new<T: Into<X>>(bed: XY, spacing: T) -> Layout {
+        Layout { bed, spacing: spacing.into() }
+    }
+
+    /// Drop each part onto z=0 and pack the footprints in rows on as many plates as needed.
+    /// Parts are packed tallest footprint first. Fails if a part is larger than the bed.
+    pub fn plates(&self, parts: Vec<D3>) -> Result<Vec<D3>> {
+        let gap = self.spacing.0;
+        let mut parts: Vec<(XYZ, D3)> = parts.into_iter().map(|part| (part.bounds().size(), part)).collect();
+        for (size, _) in &parts {
+            if size.0 > self.bed.0 || size.1 > self.bed.1 {
+                bail!("part of size {} x {} does not fit on a {} x {} bed", size.0, size.1, self.bed.0, self.bed.1);
+            }
+        }
+        parts.sort_by(|a, b| b.0 .1.total_cmp(&a.0 .1));
+
+        let mut plates: Vec<Vec<D3>> = vec![vec![]];
+        let (mut x, mut y, mut row) = (0., 0., 0.);  // next position and depth of the current row
+        for (size, part) in parts {
+            if x > 0. && x + size.0 > self.bed.0 {
+                (x, y, row) = (0., y + row + gap, 0.);
+            }
+            if y > 0. && y + size.1 > self.bed.1 {
+                plates.push(vec![]);
+                (x, y, row) = (0., 0., 0.);
+            }
+            plates.last_mut().unwrap().push(part.place_at(&[Aim::D, Aim::S, Aim::W], v3(x, y, 0)));
+            x += size.0 + gap;
+            row = f32::max(row, size.1);
+        }
+        Ok(plates.into_iter().map(|plate| plate.into_iter().union()).collect())
+    }
+
+    /// Like `plates`, but fails unless all parts fit on one plate.
+    pub fn arrange(&self, parts: Vec<D3>) -> Result<D3> {
+        let mut plates = self.plates(parts)?;
+        if plates.len() > 1 {
+            bail!("parts need {} plates of {} x {}", plates.len(), self.bed.0, self.bed.1);
+        }
+        Ok(plates.remove(0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_arrange() {
+        let layout = Layout::new(v2(10, 10), 1);
+        let plate = layout.arrange(vec![D3::cube(2), D3::cuboid(v3(3, 4, 1)), D3::sphere(1)]).unwrap();
+        assert_eq!(plate.scad(),
+            "union() {\n  translate(v = [0, 0, 0]) {\n    cube(size = [3, 4, 1]);\n  }\n  translate(v = [4, 0, 0]) {\n    cube(size = 2);\n  }\n  translate(v = [8, 1, 1]) {\n    sphere(r = 1);\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn test_plates() {
+        let layout = Layout::new(v2(10, 10), 1);
+        assert_eq!(layout.plates(vec![D3::cube(4); 9]).unwrap().len(), 3);
+        assert!(layout.arrange(vec![D3::cube(4); 9]).is_err());
+        assert!(layout.plates(vec![D3::cube(11)]).is_err());
+    }
+}
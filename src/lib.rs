@@ -2,8 +2,56 @@ mod scad2d;
 mod scad3d;
 mod cartesian;
 mod common;
+mod csg;
+mod shape;
+mod bounds;
+mod align;
+mod plane;
+mod lattice;
+mod layout;
+mod assembly;
+mod animation;
+mod params;
+mod cli;
+mod render;
+mod model;
+mod models;
+mod optimize;
+mod visit;
+mod hash;
+mod meta;
+mod bom;
+mod mesh;
+mod polygon;
+mod region;
+mod hull;
+#[cfg(feature = "serde")]
+mod json;
 
 pub use crate::cartesian::*;
 pub use crate::scad2d::*;
 pub use crate::scad3d::*;
 pub use crate::common::*;
+pub use crate::csg::*;
+pub use crate::shape::*;
+pub use crate::bounds::*;
+pub use crate::plane::*;
+pub use crate::lattice::*;
+pub use crate::layout::*;
+pub use crate::assembly::*;
+pub use crate::animation::*;
+pub use crate::params::*;
+pub use crate::cli::*;
+pub use crate::render::*;
+pub use crate::model::*;
+pub use crate::models::*;
+pub use crate::visit::*;
+pub use crate::hash::*;
+pub use crate::meta::*;
+pub use crate::bom::*;
+pub use crate::mesh::*;
+pub use crate::polygon::*;
+pub use crate::region::*;
+pub use crate::hull::*;
+#[cfg(feature = "serde")]
+pub use crate::json::*;
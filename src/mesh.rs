@@ -0,0 +1,321 @@
+//! Triangle meshes evaluated from `D3` trees, with mass properties.
+
+use crate::*;
+use std::f64::consts::PI as PI64;
+
+/// Segments around circles when tessellating spheres and cylinders.
+pub const SEGMENTS: u32 = 64;
+
+/// Closed triangle mesh with every triangle counterclockwise seen from outside.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    pub vertices: Vec<XYZ>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+fn f64s(p: XYZ) -> [f64; 3] {
+    [p.0 as f64, p.1 as f64, p.2 as f64]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Whether the bounds of `shapes` are pairwise apart, so that measurements of their union add up.
+fn apart(shapes: &[D3]) -> bool {
+    let bounds: Vec<BBox3> = shapes.iter().map(|x| x.bounds()).collect();
+    bounds.iter().enumerate().all(|(ii, a)| bounds[ii + 1..].iter().all(|b| a.intersection(*b).is_empty()))
+}
+
+impl Mesh {
+    /// A mesh from `triangles` indexing `vertices`, turned outward if they are all clockwise.
+    pub fn new(vertices: Vec<XYZ>, triangles: Vec<[usize; 3]>) -> Mesh {
+        Mesh { vertices, triangles }.oriented()
+    }
+
+    fn oriented(mut self) -> Mesh {
+        if self.signed_volume() < 0. {
+            self.triangles.iter_mut().for_each(|t| t.swap(1, 2));
+        }
+        self
+    }
+
+    /// Vertices of each triangle.
+    fn corners(&self) -> impl Iterator<Item = [[f64; 3]; 3]> + '_ {
+        self.triangles.iter().map(|t| t.map(|ii| f64s(self.vertices[ii])))
+    }
+
+    fn signed_volume(&self) -> f64 {
+        self.corners().map(|[a, b, c]| dot(a, cross(b, c))).sum::<f64>() / 6.
+    }
+
+    /// Both meshes as one, exact for measurements only when they don't overlap.
+    pub fn append(mut self, other: Mesh) -> Mesh {
+        let offset = self.vertices.len();
+        self.vertices.extend(other.vertices);
+        self.triangles.extend(other.triangles.iter().map(|t| t.map(|ii| ii + offset)));
+        self
+    }
+
+    /// Move every vertex by `f`, keeping the triangles outward when `f` reflects.
+    pub fn map<F: Fn(XYZ) -> XYZ>(mut self, f: F) -> Mesh {
+        self.vertices = self.vertices.into_iter().map(f).collect();
+        self.oriented()
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.signed_volume() as f32
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        self.corners().map(|[a, b, c]| {
+            let n = cross([b[0] - a[0], b[1] - a[1], b[2] - a[2]], [c[0] - a[0], c[1] - a[1], c[2] - a[2]]);
+            dot(n, n).sqrt() / 2.
+        }).sum::<f64>() as f32
+    }
+
+    pub fn centroid(&self) -> XYZ {
+        let (volume, moment) = self.corners().fold((0., [0.; 3]), |(v, m), [a, b, c]| {
+            let det = dot(a, cross(b, c));
+            (v + det, [0, 1, 2].map(|ii| m[ii] + det * (a[ii] + b[ii] + c[ii])))
+        });
+        let [x, y, z] = moment.map(|m| (m / volume / 4.) as f32);
+        XYZ(x, y, z)
+    }
+
+    /// Inertia tensor about the centroid for a density of 1, summed over tetrahedra from the origin.
+    pub fn inertia(&self) -> [[f32; 3]; 3] {
+        let mut second = [[0.; 3]; 3];
+        for [a, b, c] in self.corners() {
+            let det = dot(a, cross(b, c));
+            let sum = [0, 1, 2].map(|ii| a[ii] + b[ii] + c[ii]);
+            for ii in 0..3 {
+                for jj in 0..3 {
+                    let pp = a[ii] * a[jj] + b[ii] * b[jj] + c[ii] * c[jj];
+                    second[ii][jj] += det / 120. * (pp + sum[ii] * sum[jj]);
+                }
+            }
+        }
+        let volume = self.signed_volume();
+        let c = f64s(self.centroid());
+        for ii in 0..3 {
+            for jj in 0..3 {
+                second[ii][jj] -= volume * c[ii] * c[jj];
+            }
+        }
+        let trace = second[0][0] + second[1][1] + second[2][2];
+        [0, 1, 2].map(|ii| [0, 1, 2].map(|jj| (if ii == jj { trace } else { 0. } - second[ii][jj]) as f32))
+    }
+
+    /// The mesh as a SCAD polyhedron, whose faces are clockwise seen from outside.
+    pub fn polyhedron(&self) -> D3 {
+        D3::Polyhedron(
+            Box::new(self.vertices.iter().map(|p| [p.0, p.1, p.2]).collect()),
+            Box::new(self.triangles.iter().map(|t| Box::new(vec![t[0] as u32, t[2] as u32, t[1] as u32])).collect()),
+        )
+    }
+
+    pub fn cuboid(xyz: XYZ) -> Mesh {
+        let vertices = (0..8).map(|ii| XYZ(
+            if ii & 1 == 0 { 0. } else { xyz.0 },
+            if ii & 2 == 0 { 0. } else { xyz.1 },
+            if ii & 4 == 0 { 0. } else { xyz.2 },
+        )).collect();
+        let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [2, 0, 4, 6], [1, 3, 7, 5]];
+        Mesh::new(vertices, quads.iter().flat_map(|[a, b, c, d]| [[*a, *b, *c], [*a, *c, *d]]).collect())
+    }
+
+    /// Sphere of `r` with `segments` around the equator and half as many from pole to pole.
+    pub fn sphere(r: f32, segments: u32) -> Mesh {
+        let (n, m) = (segments.max(3) as usize, (segments / 2).max(2) as usize);
+        let r = r as f64;
+        let mut vertices = vec![v3(0., 0., r)];
+        for ii in 1..m {
+            let theta = PI64 * ii as f64 / m as f64;
+            vertices.extend((0..n).map(|jj| {
+                let phi = 2. * PI64 * jj as f64 / n as f64;
+                v3(r * theta.sin() * phi.cos(), r * theta.sin() * phi.sin(), r * theta.cos())
+            }));
+        }
+        vertices.push(v3(0., 0., -r));
+        let south = vertices.len() - 1;
+        let ring = |ii: usize, jj: usize| 1 + (ii - 1) * n + jj % n;
+        let mut triangles = vec![];
+        for jj in 0..n {
+            triangles.push([0, ring(1, jj), ring(1, jj + 1)]);
+            for ii in 1..m - 1 {
+                triangles.push([ring(ii, jj), ring(ii + 1, jj), ring(ii + 1, jj + 1)]);
+                triangles.push([ring(ii, jj), ring(ii + 1, jj + 1), ring(ii, jj + 1)]);
+            }
+            triangles.push([ring(m - 1, jj), south, ring(m - 1, jj + 1)]);
+        }
+        Mesh::new(vertices, triangles)
+    }
+
+    /// Cylinder of height `h` and radius `r` standing on the origin, with `segments` around.
+    pub fn cylinder(h: f32, r: f32, segments: u32) -> Mesh {
+        let n = segments.max(3) as usize;
+        let mut vertices = vec![v3(0, 0, 0), v3(0., 0., h)];
+        for z in [0., h] {
+            vertices.extend((0..n).map(|jj| {
+                let phi = 2. * std::f32::consts::PI * jj as f32 / n as f32;
+                v3(r * phi.cos(), r * phi.sin(), z)
+            }));
+        }
+        let (bottom, top) = (|jj: usize| 2 + jj % n, |jj: usize| 2 + n + jj % n);
+        let triangles = (0..n).flat_map(|jj| [
+            [0, bottom(jj + 1), bottom(jj)],
+            [1, top(jj), top(jj + 1)],
+            [top(jj), bottom(jj), bottom(jj + 1)],
+            [top(jj), bottom(jj + 1), top(jj + 1)],
+        ]).collect();
+        Mesh::new(vertices, triangles)
+    }
+
+    /// A polyhedron with faces fanned into triangles.
+    pub fn polyhedron_faces(points: &[[f32; 3]], faces: &[Box<Vec<u32>>]) -> Mesh {
+        let triangles = faces.iter().flat_map(|face| (1..face.len().saturating_sub(1))
+            .map(move |jj| [face[0] as usize, face[jj] as usize, face[jj + 1] as usize])).collect();
+        Mesh::new(points.iter().map(|p| XYZ(p[0], p[1], p[2])).collect(), triangles)
+    }
+}
+
+impl D3 {
+    /// Tessellate the solid with `segments` around circles.
+    /// `None` for unions of parts whose bounds meet, other booleans, extrusions and animations,
+    /// which need a CSG evaluation.
+    pub fn mesh(&self, segments: u32) -> Option<Mesh> {
+        match self {
+            D3::Cube(X(s, _)) => Some(Mesh::cuboid(v3(*s, *s, *s))),
+            D3::Cuboid(xyz) => Some(Mesh::cuboid(*xyz)),
+            D3::Sphere(X(r, _)) => Some(Mesh::sphere(*r, segments)),
+            D3::Cylinder(X(h, _), X(r, _)) => Some(Mesh::cylinder(*h, *r, segments)),
+            D3::Polyhedron(points, faces) => Some(Mesh::polyhedron_faces(points, faces)),
+            D3::Color(_, shape) | D3::Named(_, shape) => shape.mesh(segments),
+            D3::Translate(xyz, shape) => shape.mesh(segments).map(|m| m.map(|p| p + *xyz)),
+            D3::Rotate(xyz, shape) => shape.mesh(segments).map(|m| m.map(|p| p.rotate(*xyz))),
+            D3::Mirror(n, shape) => shape.mesh(segments).map(|m| m.map(|p| p - *n * (2. * p.dot(*n) / n.dot(*n)))),
+            D3::Scale(X(s, _), shape) => shape.mesh(segments).map(|m| m.map(|p| p * *s)),
+            D3::Scale3(XYZ(x, y, z), shape) => shape.mesh(segments).map(|m| m.map(|p| XYZ(p.0 * x, p.1 * y, p.2 * z))),
+            D3::Join(BoolOp::Union, v) if apart(v) => v.iter().map(|x| x.mesh(segments))
+                .reduce(|a, b| Some(a?.append(b?))).unwrap_or(Some(Mesh::default())),
+            D3::LinearExtrude(_, _) | D3::RotateExtrude(_, _) | D3::Difference(_, _) | D3::Join(_, _)
+                | D3::Animate(_, _) => None,
+        }
+    }
+
+    /// Volume, exact for primitives and polyhedra under transforms.
+    /// Unions are only measured when the bounds of their parts are apart.
+    pub fn volume(&self) -> Option<f32> {
+        match self {
+            D3::Cube(X(s, _)) => Some(s * s * s),
+            D3::Cuboid(XYZ(x, y, z)) => Some(x * y * z),
+            D3::Sphere(X(r, _)) => Some(4. / 3. * PI.0 * r * r * r),
+            D3::Cylinder(X(h, _), X(r, _)) => Some(PI.0 * r * r * h),
+            D3::Color(_, shape) | D3::Named(_, shape) | D3::Translate(_, shape) | D3::Rotate(_, shape)
+                | D3::Mirror(_, shape) | D3::Animate(_, shape) => shape.volume(),
+            D3::Scale(X(s, _), shape) => shape.volume().map(|v| v * (s * s * s).abs()),
+            D3::Scale3(XYZ(x, y, z), shape) => shape.volume().map(|v| v * (x * y * z).abs()),
+            D3::Join(BoolOp::Union, v) if apart(v) => v.iter().map(|x| x.volume()).sum(),
+            D3::LinearExtrude(X(h, _), profile) => profile.area().map(|a| a * h),
+            D3::RotateExtrude(X(angle, _), profile) => Some(profile.area()? * profile.centroid()?.0.abs() * angle.to_radians()),
+            shape => shape.mesh(SEGMENTS).map(|m| m.volume()),
+        }
+    }
+
+    /// Surface area, exact for primitives and polyhedra under rigid motions and uniform scaling.
+    pub fn surface_area(&self) -> Option<f32> {
+        match self {
+            D3::Cube(X(s, _)) => Some(6. * s * s),
+            D3::Cuboid(XYZ(x, y, z)) => Some(2. * (x * y + y * z + z * x)),
+            D3::Sphere(X(r, _)) => Some(4. * PI.0 * r * r),
+            D3::Cylinder(X(h, _), X(r, _)) => Some(2. * PI.0 * r * (r + h)),
+            D3::Color(_, shape) | D3::Named(_, shape) | D3::Translate(_, shape) | D3::Rotate(_, shape)
+                | D3::Mirror(_, shape) | D3::Animate(_, shape) => shape.surface_area(),
+            D3::Scale(X(s, _), shape) => shape.surface_area().map(|a| a * s * s),
+            D3::Join(BoolOp::Union, v) if apart(v) => v.iter().map(|x| x.surface_area()).sum(),
+            D3::LinearExtrude(X(h, _), profile) => Some(2. * profile.area()? + profile.perimeter()? * h),
+            shape => shape.mesh(SEGMENTS).map(|m| m.surface_area()),
+        }
+    }
+
+    /// Center of mass for a uniform density, exact where `volume` is.
+    pub fn centroid(&self) -> Option<XYZ> {
+        match self {
+            D3::Cube(X(s, _)) => Some(v3(*s, *s, *s) * 0.5),
+            D3::Cuboid(xyz) => Some(*xyz * 0.5),
+            D3::Sphere(_) => Some(v3(0, 0, 0)),
+            D3::Cylinder(X(h, _), _) => Some(v3(0., 0., h / 2.)),
+            D3::Color(_, shape) | D3::Named(_, shape) => shape.centroid(),
+            D3::Translate(xyz, shape) => shape.centroid().map(|p| p + *xyz),
+            D3::Rotate(xyz, shape) => shape.centroid().map(|p| p.rotate(*xyz)),
+            D3::Mirror(n, shape) => shape.centroid().map(|p| p - *n * (2. * p.dot(*n) / n.dot(*n))),
+            D3::Scale(X(s, _), shape) => shape.centroid().map(|p| p * *s),
+            D3::Scale3(XYZ(x, y, z), shape) => shape.centroid().map(|p| XYZ(p.0 * x, p.1 * y, p.2 * z)),
+            D3::Join(BoolOp::Union, v) if apart(v) => {
+                let (volume, moment) = v.iter().try_fold((0., v3(0, 0, 0)), |(volume, moment), x| {
+                    let (v, c) = (x.volume()?, x.centroid()?);
+                    Some((volume + v, moment + c * v))
+                })?;
+                Some(moment * (1. / volume))
+            },
+            D3::LinearExtrude(X(h, _), profile) => profile.centroid().map(|c| XYZ(c.0, c.1, h / 2.)),
+            shape => shape.mesh(SEGMENTS).map(|m| m.centroid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-3 * b.abs().max(1.), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_mesh_cuboid() {
+        let mesh = D3::cuboid(v3(2, 3, 4)).translate(v3(1, 1, 1)).mesh(SEGMENTS).unwrap();
+        assert_near(mesh.volume(), 24.);
+        assert_near(mesh.surface_area(), 52.);
+        assert_eq!(mesh.centroid(), v3(2., 2.5, 3.));
+        let inertia = mesh.inertia();
+        assert_near(inertia[0][0], 24. * (9. + 16.) / 12.);
+        assert_near(inertia[2][2], 24. * (4. + 9.) / 12.);
+        assert_near(inertia[0][1], 0.);
+    }
+
+    #[test]
+    fn test_mesh_sphere_cylinder() {
+        let sphere = Mesh::sphere(2., 128);
+        assert!((sphere.volume() - D3::sphere(2).volume().unwrap()).abs() < 0.1);
+        let cylinder = D3::cylinder(4, 1).mirror(v3(0, 0, 1)).mesh(256).unwrap();
+        assert!(cylinder.volume() > 0.);
+        assert!((cylinder.volume() - 4. * PI.0).abs() < 0.01);
+        assert_near(cylinder.centroid().2, -2.);
+    }
+
+    #[test]
+    fn test_closed_form() {
+        let shape = D3::cube(2).add(D3::sphere(1).translate(v3(5, 1, 1))).scale(2);
+        assert_near(shape.volume().unwrap(), 8. * (8. + 4. / 3. * PI.0));
+        assert_near(shape.surface_area().unwrap(), 4. * (24. + 4. * PI.0));
+        assert_near(shape.centroid().unwrap().1, 2.);
+        assert_eq!(D3::cube(2).intersection(D3::sphere(1)).volume(), None);
+    }
+
+    #[test]
+    fn test_overlapping_union() {
+        let overlap = D3::cube(2).add(D3::cube(2));
+        assert_eq!(overlap.volume(), None);
+        assert_eq!(overlap.surface_area(), None);
+        assert_eq!(overlap.centroid(), None);
+        assert!(overlap.mesh(SEGMENTS).is_none());
+        assert_eq!(D3::cube(2).add(D3::cube(2).translate(v3(2, 0, 0))).surface_area(), None);
+        assert_eq!(D3::cube(2).add(D3::cube(2).translate(v3(3, 0, 0))).volume(), Some(16.));
+    }
+}
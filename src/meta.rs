@@ -0,0 +1,108 @@
+//! Names and metadata attached to subtrees, such as material, print orientation or quantity.
+
+use crate::*;
+
+/// A label for a subtree with `key = value` data. The SCAD emitter writes it as a comment above the
+/// subtree, there is no exporter that turns it into object names yet.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Meta {
+    pub name: String,
+    pub data: Vec<(String, String)>,
+}
+
+impl Meta {
+    pub fn new(name: &str) -> Meta {
+        Meta { name: name.to_string(), data: vec![] }
+    }
+
+    /// Set `key` to `value`, replacing an earlier value.
+    pub fn with(mut self, key: &str, value: &str) -> Meta {
+        match self.data.iter_mut().find(|(k, _)| k == key) {
+            Some(entry) => entry.1 = value.to_string(),
+            None => self.data.push((key.to_string(), value.to_string())),
+        }
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.data.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// The SCAD comment line, such as `// base: material = PLA, quantity = 2`.
+    /// Line breaks are written as `\n` and `\r` so the comment stays on one line.
+    pub fn comment(&self) -> String {
+        let escape = |s: &str| s.replace('\n', "\\n").replace('\r', "\\r");
+        let data = self.data.iter().map(|(k, v)| format!("{} = {}", escape(k), escape(v))).collect::<Vec<_>>().join(", ");
+        match (self.name.is_empty(), data.is_empty()) {
+            (_, true) => format!("// {}", escape(&self.name)),
+            (true, false) => format!("// {}", data),
+            (false, false) => format!("// {}: {}", escape(&self.name), data),
+        }
+    }
+}
+
+impl D3 {
+    /// Label the shape with `name`, keeping any data already attached to it.
+    pub fn named(self, name: &str) -> D3 {
+        match self {
+            D3::Named(meta, shape) => D3::Named(Meta { name: name.to_string(), ..meta }, shape),
+            shape => D3::Named(Meta::new(name), Box::new(shape)),
+        }
+    }
+
+    /// Attach `key = value` to the shape's label, adding an unnamed label if it has none.
+    pub fn with_meta(self, key: &str, value: &str) -> D3 {
+        match self {
+            D3::Named(meta, shape) => D3::Named(meta.with(key, value), shape),
+            shape => D3::Named(Meta::default().with(key, value), Box::new(shape)),
+        }
+    }
+
+    /// The label directly on this shape, if any.
+    pub fn meta(&self) -> Option<&Meta> {
+        match self {
+            D3::Named(meta, _) => Some(meta),
+            _ => None,
+        }
+    }
+
+    /// The first subtree labeled `name`, depth first, without its label.
+    pub fn find_named(&self, name: &str) -> Option<&D3> {
+        self.find(|node| node.as_d3().and_then(|d3| d3.meta()).is_some_and(|meta| meta.name == name))
+            .and_then(|node| node.as_d3())
+            .and_then(|d3| d3.children().pop())
+    }
+
+    /// Every label in the tree, depth first.
+    pub fn labels(&self) -> Vec<&Meta> {
+        self.fold(vec![], |mut labels, node| {
+            labels.extend(node.as_d3().and_then(|d3| d3.meta()));
+            labels
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_named_scad() {
+        let shape = D3::cube(2).named("base").with_meta("material", "PLA").with_meta("quantity", "2");
+        assert_eq!(shape.scad(), "// base: material = PLA, quantity = 2\ncube(size = 2);");
+        assert_eq!(D3::sphere(1).named("ball").add(D3::cube(1)).scad(),
+            "union() {\n  // ball\n  sphere(r = 1);\n  cube(size = 1);\n}");
+        assert_eq!(D3::cube(1).named("a\nb").with_meta("note", "c\r\nd").scad(), "// a\\nb: note = c\\r\\nd\ncube(size = 1);");
+    }
+
+    #[test]
+    fn test_find_named() {
+        let model = D3::cube(2).named("base")
+            .add(D3::sphere(1).named("cutter").with_meta("quantity", "4").translate(v3(0, 0, 2)));
+        assert_eq!(model.find_named("cutter"), Some(&D3::sphere(1)));
+        assert_eq!(model.find_named("lid"), None);
+        assert_eq!(model.labels().iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), vec!["base", "cutter"]);
+        assert_eq!(model.labels()[1].get("quantity"), Some("4"));
+    }
+}
@@ -0,0 +1,97 @@
+//! Models with named parameters, and a registry to find them by name.
+
+use crate::*;
+use anyhow::Result;
+
+/// A reusable model that builds its shape from parameters.
+pub trait Model {
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    /// The parameters with their defaults.
+    fn params(&self) -> Params {
+        Params::new()
+    }
+
+    fn build(&self, params: &Params) -> D3;
+
+    /// The shape with the default parameters.
+    fn shape(&self) -> D3 {
+        self.build(&self.params())
+    }
+
+    /// SCAD of the default shape with the Customizer declarations of the parameters it is written in terms of.
+    fn scad(&self) -> String {
+        Cli { params: self.params(), out: None, list_params: false }.build(|params| self.build(params))
+    }
+}
+
+/// Main of a model binary, see `run_model`.
+pub fn run(model: &dyn Model) -> Result<()> {
+    run_model(model.params(), |params| model.build(params))
+}
+
+/// Models by name, in the order they were registered.
+#[derive(Default)]
+pub struct Registry {
+    pub models: Vec<Box<dyn Model>>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry { models: vec![] }
+    }
+
+    /// The models that ship with the library.
+    pub fn builtin() -> Registry {
+        Registry::new()
+            .register(G4gLogo)
+            .register(InvertibleHeartPlate)
+            .register(Racetrack)
+    }
+
+    pub fn register<M: Model + 'static>(mut self, model: M) -> Registry {
+        self.models.push(Box::new(model));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Model> {
+        self.models.iter().find(|m| m.name() == name).map(|m| m.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.models.iter().map(|m| m.name()).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Block;
+
+    impl Model for Block {
+        fn name(&self) -> &'static str { "block" }
+        fn description(&self) -> &'static str { "A cube with a racetrack on top" }
+        fn params(&self) -> Params { Params::new().with(Param::new("side", 60)) }
+        fn build(&self, params: &Params) -> D3 {
+            let side = params.x("side");
+            D3::cube(&side).add(racetrack(&side / 4).translate(v3(side.0 / 2., side.0 / 2., side.0)))
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = Registry::builtin().register(Block);
+        assert_eq!(registry.names(), vec!["g4g_logo", "invertible_heart_plate", "racetrack", "block"]);
+        let block = registry.get("block").unwrap();
+        assert!(block.shape().scad().starts_with("union() {\n  cube(size = side);"));
+        assert!(block.scad().starts_with("// Not customizable, baked into vectors: side = 60\nunion() {\n  cube(size = 60);"), "the translation bakes side in");
+        assert!(registry.get("missing").is_none());
+        for model in &Registry::builtin().models {
+            assert_eq!(model.params().baked(|params| model.build(params)), Vec::<&str>::new(), "{}", model.name());
+        }
+        assert!(registry.get("invertible_heart_plate").unwrap().scad().starts_with("// Height of the extruded heart (mm)\nthickness = 3;"));
+    }
+}
@@ -0,0 +1,90 @@
+//! Models that ship with the library.
+
+use crate::*;
+
+/// Oval track of two circles of diameter `r/2` hulled together.
+pub fn racetrack(r: X) -> D3 {
+    D2::circle(0.5*r.clone())
+        .translate( (0., r*PI/4.) )
+        .iter_rotate_equal(2)
+        .hull()
+        .linear_extrude(10)
+}
+
+/// Heart outline that can be turned inside out.
+pub fn invertible_heart(r: X) -> D2 {
+    let theta = (2.0/PI).atan()*180./PI;
+    D2::circle(0.5*r.clone())
+        .translate(v2(0., r*PI/4.))
+        .rotate(theta)
+        .iter_rotate_equal(2)
+        .hull()
+        .intersection(D2::half_plane(Aim::W))
+        .add_map(|x| x.mirror(v2(1., 0.)))
+}
+
+/// The Gathering 4 Gardner `G` logo, 16 units tall before scaling.
+pub fn g4g_logo<T: Into<X>>(scale: T) -> D2 {
+    let sq3 = f32::powf(3.0, 0.5);
+    D2::polygon(vec![
+        v2(0,0), v2(sq3,1), v2(4.*sq3,1.), v2(4.*sq3,-4),
+        v2(0,-8), v2(-4.*sq3,-4), v2(-4.*sq3,4),
+        v2(0,8), v2(4.*sq3,4), v2(3.*sq3,3),
+        v2(0,6), v2(-3.*sq3,3), v2(-3.*sq3,-3),
+        v2(0,-6), v2(3.*sq3,-3), v2(3.*sq3,-1), v2(sq3,-1),
+        ])
+        .scale(scale)
+}
+
+pub struct Racetrack;
+
+impl Model for Racetrack {
+    fn name(&self) -> &'static str { "racetrack" }
+
+    fn description(&self) -> &'static str { "Oval track of two hulled circles" }
+
+    fn params(&self) -> Params {
+        Params::new().with(Param::new("r", 25).range(5, 1, 100).unit("mm").description("Radius of the track ends").numeric())
+    }
+
+    fn build(&self, params: &Params) -> D3 {
+        racetrack(params.x("r"))
+    }
+}
+
+/// The `invertible_heart` outline extruded into a plate.
+pub struct InvertibleHeartPlate;
+
+impl Model for InvertibleHeartPlate {
+    fn name(&self) -> &'static str { "invertible_heart_plate" }
+
+    fn description(&self) -> &'static str { "Plate of a heart that can be turned inside out" }
+
+    fn params(&self) -> Params {
+        Params::new()
+            .with(Param::new("r", 25).range(5, 1, 100).unit("mm").description("Radius of the heart lobes").numeric())
+            .with(Param::new("thickness", 3).range(0.5, 0.5, 20).unit("mm").description("Height of the extruded heart"))
+    }
+
+    fn build(&self, params: &Params) -> D3 {
+        invertible_heart(params.x("r")).linear_extrude(params.x("thickness"))
+    }
+}
+
+pub struct G4gLogo;
+
+impl Model for G4gLogo {
+    fn name(&self) -> &'static str { "g4g_logo" }
+
+    fn description(&self) -> &'static str { "Gathering 4 Gardner logo" }
+
+    fn params(&self) -> Params {
+        Params::new()
+            .with(Param::new("scale", 5).range(1, 0.5, 20).description("Scale of the 16 unit tall logo"))
+            .with(Param::new("height", 10).range(1, 1, 50).unit("mm").description("Height of the extruded logo"))
+    }
+
+    fn build(&self, params: &Params) -> D3 {
+        g4g_logo(params.x("scale")).linear_extrude(params.x("height"))
+    }
+}
@@ -0,0 +1,153 @@
+//! Simplifying shape trees before emission without changing their geometry.
+
+use crate::*;
+
+/// True for a number that is exactly `value` and does not come from a parameter.
+fn is_const(x: &X, value: f32) -> bool {
+    x.1.is_none() && x.0 == value
+}
+
+impl D3 {
+    /// Remove identity transforms, fold nested translates and scales, flatten nested unions and
+    /// intersections, drop empty children and collapse joins of a single child where that keeps the geometry.
+    pub fn optimize(self) -> D3 {
+        match self {
+            D3::Translate(xyz, shape) => match shape.optimize() {
+                D3::Translate(inner, shape) => D3::Translate(xyz + inner, shape).optimize(),
+                shape if xyz == v3(0, 0, 0) || shape.is_nothing() => shape,
+                shape => D3::Translate(xyz, Box::new(shape)),
+            },
+            D3::Rotate(xyz, shape) => match shape.optimize() {
+                shape if xyz == v3(0, 0, 0) || shape.is_nothing() => shape,
+                shape => D3::Rotate(xyz, Box::new(shape)),
+            },
+            D3::Scale(s, shape) => match shape.optimize() {
+                D3::Scale(inner, shape) => D3::Scale(s * inner, shape).optimize(),
+                shape if is_const(&s, 1.) || shape.is_nothing() => shape,
+                shape => D3::Scale(s, Box::new(shape)),
+            },
+            D3::Scale3(xyz, shape) => match shape.optimize() {
+                D3::Scale3(inner, shape) => D3::Scale3(XYZ(xyz.0 * inner.0, xyz.1 * inner.1, xyz.2 * inner.2), shape).optimize(),
+                shape if xyz == v3(1, 1, 1) || shape.is_nothing() => shape,
+                shape => D3::Scale3(xyz, Box::new(shape)),
+            },
+            D3::Mirror(xyz, shape) => D3::Mirror(xyz, Box::new(shape.optimize())),
+            D3::Color(color, shape) => D3::Color(color, Box::new(shape.optimize())),
+            D3::Animate(keys, shape) => D3::Animate(keys, Box::new(shape.optimize())),
+            D3::Named(meta, shape) => D3::Named(meta, Box::new(shape.optimize())),
+            D3::LinearExtrude(h, shape) => D3::LinearExtrude(h, Box::new(shape.optimize())),
+            D3::RotateExtrude(angle, shape) => D3::RotateExtrude(angle, Box::new(shape.optimize())),
+            D3::Join(op, v) => optimize_join(op, *v),
+            D3::Difference(a, b) => match (a.optimize(), b.optimize()) {
+                (a, _) if a.is_nothing() => a,
+                (a, b) if b.is_nothing() => a,
+                (a, b) => D3::Difference(Box::new(a), Box::new(b)),
+            },
+            shape => shape,
+        }
+    }
+}
+
+impl D2 {
+    /// Remove identity transforms, fold nested transforms, flatten nested unions and intersections,
+    /// drop empty children and collapse joins of a single child where that keeps the geometry.
+    pub fn optimize(self) -> D2 {
+        match self {
+            D2::Translate(xy, shape) => match shape.optimize() {
+                D2::Translate(inner, shape) => D2::Translate(xy + inner, shape).optimize(),
+                shape if xy == v2(0, 0) || shape.is_nothing() => shape,
+                shape => D2::Translate(xy, Box::new(shape)),
+            },
+            D2::Rotate(theta, shape) => match shape.optimize() {
+                D2::Rotate(inner, shape) => D2::Rotate(theta + inner, shape).optimize(),
+                shape if is_const(&theta, 0.) || shape.is_nothing() => shape,
+                shape => D2::Rotate(theta, Box::new(shape)),
+            },
+            D2::Scale(s, shape) => match shape.optimize() {
+                D2::Scale(inner, shape) => D2::Scale(s * inner, shape).optimize(),
+                shape if is_const(&s, 1.) || shape.is_nothing() => shape,
+                shape => D2::Scale(s, Box::new(shape)),
+            },
+            D2::Scale2(xy, shape) => match shape.optimize() {
+                D2::Scale2(inner, shape) => D2::Scale2(XY(xy.0 * inner.0, xy.1 * inner.1), shape).optimize(),
+                shape if xy == v2(1, 1) || shape.is_nothing() => shape,
+                shape => D2::Scale2(xy, Box::new(shape)),
+            },
+            D2::Mirror(xy, shape) => D2::Mirror(xy, Box::new(shape.optimize())),
+            D2::Color(color, shape) => D2::Color(color, Box::new(shape.optimize())),
+            D2::Join(op, v) => optimize_join(op, *v),
+            D2::Difference(a, b) => match (a.optimize(), b.optimize()) {
+                (a, _) if a.is_nothing() => a,
+                (a, b) if b.is_nothing() => a,
+                (a, b) => D2::Difference(Box::new(a), Box::new(b)),
+            },
+            shape => shape,
+        }
+    }
+}
+
+/// Optimize a join: splice in children joined by the same operation, drop empty children of unions and hulls,
+/// and replace a join of one child by the child. The hull of a single concave shape is not the shape, so hulls stay.
+fn optimize_join<T: Csg + Optimize>(op: BoolOp, v: Vec<T>) -> T {
+    let mut children = vec![];
+    for shape in v.into_iter().map(|x| x.optimize()) {
+        match shape.into_join() {
+            Ok((inner, v)) if inner == op => children.extend(v),
+            Ok((inner, v)) => children.push(T::join(inner, v)),
+            Err(shape) => children.push(shape),
+        }
+    }
+    if matches!(op, BoolOp::Union | BoolOp::Hull) {
+        children.retain(|x| !x.is_nothing());
+    }
+    if children.len() == 1 && op != BoolOp::Hull { children.pop().unwrap() } else { T::join(op, children) }
+}
+
+trait Optimize {
+    fn optimize(self) -> Self;
+}
+
+impl Optimize for D2 {
+    fn optimize(self) -> Self { D2::optimize(self) }
+}
+
+impl Optimize for D3 {
+    fn optimize(self) -> Self { D3::optimize(self) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_optimize_translate() {
+        let shape = D3::cube(1).iter_translate(v3(2, 0, 0), 2).union();
+        assert_eq!(shape.clone().optimize().scad(),
+            "union() {\n  cube(size = 1);\n  translate(v = [2, 0, 0]) {\n    cube(size = 1);\n  }\n}");
+        assert!(shape.clone().optimize().scad().len() < shape.scad().len());
+        assert_eq!(shape.clone().optimize().bounds(), shape.bounds());
+        assert_eq!(D3::sphere(1).translate(v3(1, 0, 0)).translate(v3(0, 2, 0)).scale(2).scale(0.5).optimize().scad(),
+            "translate(v = [1, 2, 0]) {\n  sphere(r = 1);\n}");
+    }
+
+    #[test]
+    fn test_optimize_joins() {
+        let shape = D3::cube(1).add(D3::sphere(1).add(D3::cylinder(1, 1))).add(vec![D3::cube(2)].into_iter().union())
+            .intersection(D3::cube(3).intersection(D3::sphere(3)))
+            .difference(std::iter::empty().union());
+        let optimized = shape.clone().optimize();
+        assert_eq!(optimized.scad(),
+            "intersection() {\n  union() {\n    cube(size = 1);\n    sphere(r = 1);\n    cylinder(h = 1, r = 1);\n    cube(size = 2);\n  }\n  cube(size = 3);\n  sphere(r = 3);\n}");
+        assert_eq!(optimized.bounds(), shape.bounds());
+        assert_eq!(vec![D3::cube(1)].into_iter().hull().optimize().scad(), "hull() {\n  cube(size = 1);\n}");
+    }
+
+    #[test]
+    fn test_optimize_d2() {
+        let shape = D2::circle(2).rotate(0).translate(v2(0, 0)).add(D2::square(1).add(D2::square(3)));
+        assert_eq!(shape.optimize().scad(),
+            "union() {\n  circle(d = 2);\n  square(size = 1);\n  square(size = 3);\n}");
+        assert_eq!(D2::circle(1).scale(Param::new("k", 1).x()).optimize().scad(),
+            "scale(v = k) {\n  circle(d = 1);\n}");
+    }
+}
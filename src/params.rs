@@ -0,0 +1,274 @@
+//! Named parameters that stay symbolic in the SCAD output and show up in OpenSCAD's Customizer.
+
+use crate::*;
+use anyhow::{anyhow, bail, Context, Result};
+
+/// A named number. `x()` gives an `X` that is emitted as the name, and as expressions of it after arithmetic.
+/// Only `X` values carry expressions, so a parameter used in a vector such as a translation is baked
+/// into the SCAD as numbers and not declared, see `Params::bake`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+    pub name: &'static str,
+    pub value: f32,
+    /// Whether the parameter is declared as a Customizer variable, otherwise `x()` is a plain number.
+    pub customizer: bool,
+    /// Customizer slider as `(min, step, max)`.
+    pub range: Option<(f32, f32, f32)>,
+    pub group: Option<&'static str>,
+    pub description: Option<&'static str>,
+    pub unit: Option<&'static str>,
+}
+
+impl Param {
+    pub fn new<T: Into<X>>(name: &'static str, value: T) -> Param {
+        Param { name, value: value.into().0, customizer: true, range: None, group: None, description: None, unit: None }
+    }
+
+    /// Only set from the command line and baked into the SCAD, for models that are not written in terms of it.
+    pub fn numeric(mut self) -> Param {
+        self.customizer = false;
+        self
+    }
+
+    pub fn range<T0: Into<X>, T1: Into<X>, T2: Into<X>>(mut self, min: T0, step: T1, max: T2) -> Param {
+        self.range = Some((min.into().0, step.into().0, max.into().0));
+        self
+    }
+
+    pub fn group(mut self, group: &'static str) -> Param {
+        self.group = Some(group);
+        self
+    }
+
+    pub fn description(mut self, description: &'static str) -> Param {
+        self.description = Some(description);
+        self
+    }
+
+    pub fn unit(mut self, unit: &'static str) -> Param {
+        self.unit = Some(unit);
+        self
+    }
+
+    /// Set the value, failing if it is outside the range.
+    pub fn set(&mut self, value: f32) -> Result<()> {
+        if let Some((min, _, max)) = self.range {
+            if value < min || value > max {
+                bail!("{} = {} is outside of [{}, {}]", self.name, value, min, max);
+            }
+        }
+        self.value = value;
+        Ok(())
+    }
+
+    pub fn x(&self) -> X {
+        X(self.value, self.customizer.then(|| self.name.into()))
+    }
+
+    /// A different value within the range, one step away from the current one.
+    fn probe(&self) -> f32 {
+        match self.range {
+            None => self.value + 1.,
+            Some((min, step, max)) => {
+                let step = if step > 0. { step } else { (max - min) / 2. };
+                if self.value + step <= max { self.value + step } else { (self.value - step).max(min) }
+            },
+        }
+    }
+
+    /// Customizer declaration of the parameter.
+    pub fn scad(&self) -> String {
+        let description = match (self.description, self.unit) {
+            (Some(d), Some(u)) => format!("// {} ({})\n", d, u),
+            (Some(d), None) => format!("// {}\n", d),
+            (None, Some(u)) => format!("// ({})\n", u),
+            (None, None) => String::new(),
+        };
+        let range = self.range.map(|(min, step, max)| format!(" // [{}:{}:{}]", min, step, max)).unwrap_or_default();
+        format!("{}{} = {};{}", description, self.name, self.value, range)
+    }
+}
+
+/// An ordered set of parameters.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Params {
+    pub params: Vec<Param>,
+}
+
+impl Params {
+    pub fn new() -> Params {
+        Params { params: vec![] }
+    }
+
+    pub fn with(mut self, param: Param) -> Params {
+        self.params.push(param);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Param> {
+        self.params.iter().find(|p| p.name == name)
+    }
+
+    /// The symbolic value of parameter `name`. Panics if there is no such parameter.
+    pub fn x(&self, name: &str) -> X {
+        self.get(name).unwrap_or_else(|| panic!("unknown parameter {}", name)).x()
+    }
+
+    /// The number of a `numeric` parameter `name`. Panics if there is no such parameter.
+    pub fn value(&self, name: &str) -> f32 {
+        self.get(name).unwrap_or_else(|| panic!("unknown parameter {}", name)).value
+    }
+
+    /// Names of the Customizer parameters that `build` turns into plain numbers somewhere, for instance
+    /// in a vector. Found by building again with each value moved one step within its range and
+    /// comparing the SCAD.
+    pub fn baked<F: Fn(&Params) -> D3>(&self, build: F) -> Vec<&'static str> {
+        let scad = build(self).scad();
+        self.params.iter().enumerate()
+            .filter(|(_, p)| p.customizer)
+            .filter(|(ii, p)| {
+                let mut changed = self.clone();
+                changed.params[*ii].value = p.probe();
+                build(&changed).scad() != scad
+            })
+            .map(|(_, p)| p.name)
+            .collect()
+    }
+
+    /// The parameters with the `baked` ones made `numeric`, so that no declaration is left
+    /// that the model does not follow.
+    pub fn bake<F: Fn(&Params) -> D3>(mut self, build: F) -> Params {
+        let baked = self.baked(build);
+        for param in self.params.iter_mut().filter(|p| baked.contains(&p.name)) {
+            param.customizer = false;
+        }
+        self
+    }
+
+    /// Set parameter `name` to `value`, failing if it is unknown or out of range.
+    pub fn set(&mut self, name: &str, value: f32) -> Result<()> {
+        self.params.iter_mut().find(|p| p.name == name)
+            .ok_or_else(|| anyhow!("unknown parameter {}", name))?
+            .set(value)
+    }
+
+    /// Set a parameter from `name=value`.
+    pub fn set_str(&mut self, assignment: &str) -> Result<()> {
+        let (name, value) = assignment.split_once('=')
+            .ok_or_else(|| anyhow!("expected name=value, got {}", assignment))?;
+        let value = value.trim().parse::<f32>().with_context(|| format!("bad value for {}", name))?;
+        self.set(name.trim(), value)
+    }
+
+    /// Set parameters from JSON, either an object of values or an OpenSCAD Customizer file
+    /// `{"parameterSets": {"set": {...}}}` where `set` picks the parameter set.
+    /// Values may be numbers or strings holding numbers, as the Customizer writes them.
+    pub fn set_json(&mut self, json: &str, set: Option<&str>) -> Result<()> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let values = match value.get("parameterSets").and_then(|sets| sets.as_object()) {
+            None => &value,
+            Some(sets) => match set {
+                Some(set) => sets.get(set).ok_or_else(|| anyhow!("no parameter set {}", set))?,
+                None if sets.len() == 1 => sets.values().next().unwrap(),
+                None => bail!("pick one of the parameter sets {:?}", sets.keys().collect::<Vec<_>>()),
+            },
+        };
+        let values = values.as_object().ok_or_else(|| anyhow!("expected an object of parameter values"))?;
+        for (name, value) in values {
+            let number = match value {
+                serde_json::Value::Number(n) => n.as_f64().map(|n| n as f32),
+                serde_json::Value::String(s) => s.trim().parse::<f32>().ok(),
+                _ => None,
+            }.ok_or_else(|| anyhow!("bad value for {}: {}", name, value))?;
+            self.set(name, number)?;
+        }
+        Ok(())
+    }
+
+    /// One line per parameter with its value, unit, range and description.
+    pub fn list(&self) -> String {
+        self.params.iter().map(|p| {
+            let mut line = format!("{} = {}", p.name, p.value);
+            if let Some(unit) = p.unit { line += &format!(" {}", unit); }
+            if let Some((min, step, max)) = p.range { line += &format!(" [{}:{}:{}]", min, step, max); }
+            if let Some(group) = p.group { line += &format!(" ({})", group); }
+            if let Some(description) = p.description { line += &format!("  {}", description); }
+            line
+        }).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Top level variable declarations with Customizer annotations, in order.
+    /// A group tab is started whenever the group changes.
+    pub fn scad(&self) -> String {
+        let mut group = None;
+        let mut lines = vec![];
+        for param in self.params.iter().filter(|p| p.customizer) {
+            if param.group.is_some() && param.group != group {
+                group = param.group;
+                lines.push(format!("/* [{}] */", group.unwrap()));
+            }
+            lines.push(param.scad());
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_expressions() {
+        let gap = Param::new("gap", 0.2).x();
+        assert_eq!(format!("{}", 2 * gap.clone() + 1), "(2 * gap) + 1");
+        assert_eq!((2 * gap.clone() + 1).0, 1.4);
+        assert_eq!(D3::cylinder(10, &gap / 2).scad(), "cylinder(h = 10, r = gap / 2);");
+        assert_eq!(format!("{}", -gap), "-gap");
+    }
+
+    #[test]
+    fn test_customizer() {
+        let params = Params::new()
+            .with(Param::new("size", 20).range(10, 1, 50).description("Edge of the cube"))
+            .with(Param::new("gap", 0.2).group("Fit"));
+        assert_eq!(params.scad(),
+            "// Edge of the cube\nsize = 20; // [10:1:50]\n/* [Fit] */\ngap = 0.2;");
+        assert_eq!(D3::cube(params.x("size")).scad(), "cube(size = size);");
+    }
+
+    #[test]
+    fn test_baked() {
+        let params = Params::new().with(Param::new("w", 4)).with(Param::new("h", 2)).with(Param::new("n", 1).numeric());
+        let build = |p: &Params| D3::cube(p.x("h")).translate(v3(p.x("w") * p.x("n"), 0, 0));
+        assert_eq!(params.baked(build), vec!["w"]);
+        let baked = params.bake(build);
+        assert_eq!(baked.scad(), "h = 2;");
+        assert_eq!(build(&baked).scad(), "translate(v = [4, 0, 0]) {\n  cube(size = h);\n}");
+    }
+
+    #[test]
+    fn test_probe() {
+        assert_eq!(Param::new("n", 3).probe(), 4.);
+        assert_eq!(Param::new("n", 3).range(1, 0.5, 10).probe(), 3.5);
+        assert_eq!(Param::new("n", 10).range(1, 0.5, 10).probe(), 9.5);
+        assert_eq!(Param::new("n", 1).range(1, 5, 2).probe(), 1.);
+        let params = Params::new().with(Param::new("w", 10).range(0, 1, 10));
+        assert_eq!(params.baked(|p| D3::cube(1).translate(v3(p.x("w"), 0, 0))), vec!["w"]);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut params = Params::new()
+            .with(Param::new("gap", 0.1).range(0, 0.05, 1).unit("mm"))
+            .with(Param::new("l_edge", 8).unit("mm"));
+        params.set_str("gap=0.15").unwrap();
+        assert_eq!(params.x("gap").expr(), Some("gap"));
+        assert_eq!(params.value("gap"), 0.15);
+        assert!(params.set_str("gap=2").is_err());
+        assert!(params.set_str("bevel=1").is_err());
+        params.set_json(r#"{"parameterSets": {"big": {"l_edge": "12"}}, "fileFormatVersion": "1"}"#, None).unwrap();
+        params.set_json(r#"{"gap": 0.3}"#, None).unwrap();
+        assert_eq!(params.list(), "gap = 0.3 mm [0:0.05:1]\nl_edge = 12 mm");
+        assert_eq!(params.params[1].scad(), "// (mm)\nl_edge = 12;");
+    }
+}
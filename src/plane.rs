@@ -0,0 +1,259 @@
+//! Planes in space and cutting shapes with them.
+
+use crate::*;
+
+/// Plane through `point` with the positive side in the direction of `normal`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    pub point: XYZ,
+    pub normal: XYZ,
+}
+
+impl Plane {
+    pub fn new(point: XYZ, normal: XYZ) -> Plane {
+        Plane { point, normal }
+    }
+
+    /// Plane through `point` facing the direction of `aim`.
+    pub fn aim(point: XYZ, aim: Aim) -> Plane {
+        Plane::new(point, aim.xyz())
+    }
+
+    /// Horizontal plane at height `z` facing up.
+    pub fn z<T: Into<X>>(z: T) -> Plane {
+        Plane::new(v3(0, 0, z), v3(0, 0, 1))
+    }
+
+    /// The same plane with the sides swapped.
+    pub fn flip(&self) -> Plane {
+        Plane::new(self.point, -self.normal)
+    }
+
+    /// Distance of `xyz` from the plane, positive on the side of the normal.
+    pub fn distance(&self, xyz: XYZ) -> f32 {
+        (xyz - self.point).dot(self.normal) / self.normal.norm()
+    }
+
+    /// Move `shape` from the frame of the plane, where the plane is XY with the normal up, into space.
+    pub fn place(&self, shape: D3) -> D3 {
+        let rotation = self.normal.rotation_from_z();
+        if rotation.1 == 0. {
+            shape.translate(self.point)
+        } else {
+            shape.rotate(rotation).translate(self.point)
+        }
+    }
+
+    /// Move `shape` from space into the frame of the plane, the inverse of `place`.
+    pub fn unplace(&self, shape: D3) -> D3 {
+        let XYZ(_, theta, phi) = self.normal.rotation_from_z();
+        let shape = shape.translate(-self.point);
+        if theta == 0. {
+            shape
+        } else {
+            shape.rotate(v3(0, 0, -phi)).rotate(v3(0, -theta, 0))
+        }
+    }
+
+    /// A cuboid covering the positive side of the plane within distance `reach` of its point.
+    pub fn half_space<T: Into<X>>(&self, reach: T) -> D3 {
+        let r = reach.into().0;
+        self.place(D3::cube(2. * r).translate(v3(-r, -r, 0)))
+    }
+}
+
+impl D3 {
+    /// Keep the part of the shape on the positive side of `plane`.
+    /// The cutting cube is sized from the bounds of the shape rather than `MAX`.
+    pub fn cut(self, plane: Plane) -> D3 {
+        let bounds = self.bounds();
+        if bounds.is_empty() { return self; }
+        let reach = bounds.corners().iter().map(|c| (*c - plane.point).norm()).fold(0., f32::max) + 1.;
+        self.intersection(plane.half_space(reach))
+    }
+}
+
+/// How the two halves of `D3::split_with` are keyed together.
+/// Positions are in the frame of the cutting plane, see `Plane::place`.
+/// Keys stand on the lower half and the upper half gets sockets enlarged by `clearance`.
+#[derive(Clone, Debug)]
+pub enum Joint {
+    None,
+    /// Round pegs of `diameter` standing `length` above the cut.
+    Pegs { positions: Vec<XY>, diameter: X, length: X, clearance: X },
+    /// Dovetail rails running along Y, `width` wide at the cut and flaring by half their `height` on each side.
+    /// The sockets run out through both ends of the upper part so it can slide onto the rails.
+    Dovetails { positions: Vec<XY>, width: X, height: X, length: X, clearance: X },
+}
+
+impl Joint {
+    pub fn pegs<D: Into<X>, L: Into<X>, C: Into<X>>(positions: Vec<XY>, diameter: D, length: L, clearance: C) -> Joint {
+        Joint::Pegs { positions, diameter: diameter.into(), length: length.into(), clearance: clearance.into() }
+    }
+
+    pub fn dovetails<W: Into<X>, H: Into<X>, L: Into<X>, C: Into<X>>(positions: Vec<XY>, width: W, height: H, length: L, clearance: C) -> Joint {
+        Joint::Dovetails { positions, width: width.into(), height: height.into(), length: length.into(), clearance: clearance.into() }
+    }
+
+    /// One key or socket, grown by `c` on every side. Dovetails span `through` in Y when given.
+    fn part(&self, xy: XY, c: f32, through: Option<(f32, f32)>) -> D3 {
+        match self {
+            Joint::None => panic!("Joint::None has no parts"),
+            Joint::Pegs { diameter, length, .. } =>
+                D3::cylinder(length.0 + 2. * c, diameter.0 / 2. + c).translate(v3(xy.0, xy.1, -c)),
+            Joint::Dovetails { width, height, length, .. } => {
+                let (w, h) = (width.0 / 2. + c, height.0 + c);
+                let flare = w + height.0 / 2.;
+                let (y0, y1) = through.unwrap_or((xy.1 - length.0 / 2. - c, xy.1 + length.0 / 2. + c));
+                D2::polygon(vec![v2(-w, -c), v2(w, -c), v2(flare, h), v2(-flare, h)])
+                    .linear_extrude(y1 - y0)
+                    .rotate(v3(90, 0, 0))
+                    .translate(v3(xy.0, y1, 0))
+            },
+        }
+    }
+
+    /// Keys and sockets for a part spanning `through` in Y, or `None` when there is no joint.
+    fn parts(&self, through: (f32, f32)) -> Option<(D3, D3)> {
+        let (positions, clearance) = match self {
+            Joint::None => return None,
+            Joint::Pegs { positions, clearance, .. } => (positions, clearance.0),
+            Joint::Dovetails { positions, clearance, .. } => (positions, clearance.0),
+        };
+        Some((
+            positions.iter().map(|xy| self.part(*xy, 0., None)).union(),
+            positions.iter().map(|xy| self.part(*xy, clearance, Some(through))).union(),
+        ))
+    }
+}
+
+impl D3 {
+    /// Cut into the parts below and above `plane`, each laid on its cut face and centered on the Z axis.
+    pub fn split(self, plane: Plane) -> (D3, D3) {
+        self.split_with(plane, Joint::None)
+    }
+
+    /// Cut into the parts below and above `plane` keyed together by `joint`, both laid flat for printing.
+    /// The upper part lies on its cut face. The lower part lies on its cut face unless it carries keys,
+    /// in which case it stands on its far side with the keys pointing up.
+    pub fn split_with(self, plane: Plane, joint: Joint) -> (D3, D3) {
+        let below = plane.unplace(self.clone().cut(plane.flip()));
+        let above = plane.unplace(self.cut(plane));
+        let b = above.bounds();
+        let (below, above) = match joint.parts((b.min.1 - 1., b.max.1 + 1.)) {
+            None => (below.rotate(v3(180, 0, 0)), above),
+            Some((keys, sockets)) => (below + keys, above - sockets),
+        };
+        let seat = |shape: D3| {
+            let b = shape.bounds();
+            let c = b.center();
+            shape.translate(v3(-c.0, -c.1, -b.min.2))
+        };
+        (seat(below), seat(above))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cut() {
+        assert_eq!(D3::cube(2).cut(Plane::z(1)).scad(),
+            "intersection() {\n  cube(size = 2);\n  translate(v = [-4, -4, 1]) {\n    cube(size = 8);\n  }\n}"
+        );
+        assert_eq!(D3::cube(2).cut(Plane::z(1)).bounds(), BBox3::new(v3(0, 0, 1), v3(2, 2, 2)));
+    }
+
+    #[test]
+    fn test_cut_bounds() {
+        let b = D3::sphere(5).cut(Plane::aim(v3(0, 0, 0), Aim::W)).bounds();
+        assert!(b.min.0 == -5. && b.max.0.abs() < 1e-4, "{:?}", b);
+        let b = D3::cuboid(v3(4, 4, 40)).cut(Plane::new(v3(0, 0, 10), v3(0, 1, 1))).bounds();
+        assert!(b.min.2 < 10. && b.max.2 == 40., "{:?}", b);
+    }
+
+    #[test]
+    fn test_place_unplace() {
+        let plane = Plane::new(v3(1, 2, 3), v3(1, 1, 1));
+        let p = v3(0.5, -2, 1);
+        let rotation = plane.normal.rotation_from_z();
+        let placed = p.rotate(rotation) + plane.point;
+        assert!((plane.distance(placed) - 1.).abs() < 1e-5);
+        assert_eq!(plane.place(D3::cube(1)).scad(),
+            "translate(v = [1, 2, 3]) {\n  rotate([0, 54.735615, 45]) {\n    cube(size = 1);\n  }\n}");
+        assert_eq!(plane.unplace(D3::cube(1)).scad(),
+            "rotate([0, -54.735615, 0]) {\n  rotate([0, 0, -45]) {\n    translate(v = [-1, -2, -3]) {\n      cube(size = 1);\n    }\n  }\n}");
+    }
+
+    #[test]
+    fn test_half_plane_angle() {
+        assert_eq!(D2::half_plane(Aim::Angle(X(30., None))).scad(),
+            "rotate(30) {\n  translate(v = [0, -500]) {\n    square(size = 1000);\n  }\n}");
+        assert_eq!(D2::half_plane(Aim::N).scad(), "translate(v = [-500, 0]) {\n  square(size = 1000);\n}");
+        for aim in [Aim::N, Aim::S, Aim::E, Aim::W, Aim::U, Aim::D] {
+            assert_eq!(D2::half_plane(aim.clone()).bounds().size(), v2(MAX2, MAX2));
+            assert_eq!(D3::half_space(aim).bounds().size(), v3(MAX2, MAX2, MAX2));
+        }
+        let b = D3::half_space(Aim::Vector(v3(1, 1, 1))).bounds();
+        assert!(b.max.0 < 2. * MAX2 && b.min.0 > -2. * MAX2, "{:?}", b);
+    }
+
+    #[test]
+    fn test_d2_cut() {
+        let wide = D2::Rectangle(v2(2000, 10)).translate(v2(-1000, -5)).cut(Aim::Angle(X(90., None)));
+        let b = wide.bounds();
+        assert!(b.min.0 == -1000. && b.max.0 == 1000. && b.min.1.abs() < 1e-3, "{:?}", b);
+        assert!((wide.area().unwrap() - 10000.).abs() < 1e-2);
+        assert_eq!(D2::square(1).cut(Aim::Vector(v3(1, 0, 5))).scad(),
+            "intersection() {\n  square(size = 1);\n  translate(v = [0, -2.4142137]) {\n    square(size = 4.8284273);\n  }\n}");
+    }
+
+    #[test]
+    #[should_panic(expected = "nonzero direction")]
+    fn test_half_plane_zero_vector() {
+        D2::half_plane(Aim::Vector(v3(0, 0, 1)));
+    }
+
+    #[test]
+    fn test_half_space_vector() {
+        assert_eq!(v3(1, 0, 0).rotation_from_z(), v3(0, 90, 0));
+        assert_eq!(v3(0, -1, 0).rotation_from_z(), v3(0, 90, -90));
+        assert_eq!(Aim::Vector(v3(0, 0, 2)).xyz(), v3(0, 0, 1));
+        assert_eq!(Aim::Angle(X(90., None)).opposite(), Aim::Angle(X(270., None)));
+    }
+
+    #[test]
+    fn test_split() {
+        let (below, above) = D3::cuboid(v3(2, 2, 10)).split(Plane::z(4));
+        let b = below.bounds();
+        assert!((b.min - v3(-1, -1, 0)).norm() < 1e-5 && (b.max - v3(1, 1, 4)).norm() < 1e-5, "{:?}", b);
+        assert_eq!(above.bounds(), BBox3::new(v3(-1, -1, 0), v3(1, 1, 6)));
+    }
+
+    #[test]
+    fn test_split_tilted() {
+        let (below, above) = D3::cuboid(v3(10, 10, 10)).split(Plane::new(v3(0, 0, 5), v3(0, 1, 1)));
+        for b in [below.bounds(), above.bounds()] {
+            assert!(b.min.2.abs() < 1e-4 && b.max.2 > 1., "{:?}", b);
+            assert!((b.min.0 + b.max.0).abs() < 1e-4 && (b.min.1 + b.max.1).abs() < 1e-4, "{:?}", b);
+        }
+    }
+
+    #[test]
+    fn test_split_dovetails() {
+        let joint = Joint::dovetails(vec![v2(0, 0)], 1, 1, 4, 0.25);
+        let (_, above) = D3::cuboid(v3(2, 10, 10)).split_with(Plane::z(4), joint);
+        // The socket runs 1 past each end of the 10 long upper part.
+        assert!(above.scad().contains("linear_extrude(height = 12"), "{}", above.scad());
+    }
+
+    #[test]
+    fn test_split_pegs() {
+        let joint = Joint::pegs(vec![v2(1, 1)], 1, 2, 0.25);
+        let (below, above) = D3::cuboid(v3(2, 2, 10)).split_with(Plane::z(4), joint);
+        assert_eq!(below.bounds(), BBox3::new(v3(-1, -1, 0), v3(1, 1, 6)));
+        assert_eq!(above.bounds(), BBox3::new(v3(-1, -1, 0), v3(1, 1, 6)));
+        assert!(above.scad().contains("cylinder(h = 2.5, r = 0.75);"));
+    }
+}
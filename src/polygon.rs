@@ -0,0 +1,169 @@
+//! Measurements of polygons and `D2` profiles.
+
+use crate::*;
+
+fn cross(a: XY, b: XY) -> f32 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+/// Edges of the closed polygon through `points`.
+fn edges(points: &[XY]) -> impl Iterator<Item = (XY, XY)> + '_ {
+    points.iter().zip(points.iter().cycle().skip(1)).map(|(a, b)| (*a, *b))
+}
+
+/// Area enclosed by `points`, positive when they run counterclockwise.
+pub fn polygon_area(points: &[XY]) -> f32 {
+    edges(points).map(|(a, b)| cross(a, b)).sum::<f32>() / 2.
+}
+
+pub fn polygon_perimeter(points: &[XY]) -> f32 {
+    edges(points).map(|(a, b)| (b - a).norm()).sum()
+}
+
+pub fn polygon_centroid(points: &[XY]) -> XY {
+    let c = edges(points).fold(v2(0, 0), |c, (a, b)| c + (a + b) * cross(a, b));
+    c * (1. / (6. * polygon_area(points)))
+}
+
+/// Whether segments `ab` and `cd` share a point.
+fn segments_meet(a: XY, b: XY, c: XY, d: XY) -> bool {
+    let side = |p: XY, q: XY, r: XY| cross(q - p, r - p);
+    let within = |p: XY, q: XY, r: XY| r.0 >= p.0.min(q.0) && r.0 <= p.0.max(q.0)
+        && r.1 >= p.1.min(q.1) && r.1 <= p.1.max(q.1);
+    let (d1, d2, d3, d4) = (side(c, d, a), side(c, d, b), side(a, b, c), side(a, b, d));
+    if d1 * d2 < 0. && d3 * d4 < 0. {
+        return true;
+    }
+    (d1 == 0. && within(c, d, a)) || (d2 == 0. && within(c, d, b))
+        || (d3 == 0. && within(a, b, c)) || (d4 == 0. && within(a, b, d))
+}
+
+/// Whether the closed polygon through `points` has at least three corners and no edges that
+/// cross, touch or fold back, as OpenSCAD expects.
+pub fn polygon_is_simple(points: &[XY]) -> bool {
+    let n = points.len();
+    if n < 3 || polygon_area(points) == 0. {
+        return false;
+    }
+    let edge = |ii: usize| (points[ii], points[(ii + 1) % n]);
+    (0..n).all(|ii| {
+        let (a, b) = edge(ii);
+        let (_, c) = edge((ii + 1) % n);
+        let folds = cross(b - a, c - b) == 0. && (b - a).dot(c - b) <= 0.;
+        !folds && (ii + 2..n).filter(|jj| (jj + 1) % n != ii).all(|jj| {
+            let (c, d) = edge(jj);
+            !segments_meet(a, b, c, d)
+        })
+    })
+}
+
+fn reflect(p: XY, n: XY) -> XY {
+    p - n * (2. * p.dot(n) / n.dot(n))
+}
+
+impl D2 {
+    /// Area of the profile, exact for primitives and polygons under transforms.
+    /// Booleans are measured on their evaluated `regions`.
+    pub fn area(&self) -> Option<f32> {
+        match self {
+            D2::Circle(X(d, _)) => Some(PI.0 * d * d / 4.),
+            D2::Square(X(s, _)) => Some(s * s),
+            D2::Rectangle(XY(x, y)) => Some(x * y),
+            D2::Polygon(points) => Some(polygon_area(points).abs()),
+            D2::Color(_, shape) | D2::Rotate(_, shape) | D2::Translate(_, shape) | D2::Mirror(_, shape) => shape.area(),
+            D2::Scale(X(s, _), shape) => shape.area().map(|a| a * s * s),
+            D2::Scale2(XY(x, y), shape) => shape.area().map(|a| a * (x * y).abs()),
+            D2::Join(_, _) | D2::Difference(_, _) => Some(self.regions().iter().map(Region::area).sum()),
+        }
+    }
+
+    /// Length of the outline including holes, exact where `area` is.
+    pub fn perimeter(&self) -> Option<f32> {
+        match self {
+            D2::Circle(X(d, _)) => Some(PI.0 * d),
+            D2::Square(X(s, _)) => Some(4. * s),
+            D2::Rectangle(XY(x, y)) => Some(2. * (x + y)),
+            D2::Polygon(points) => Some(polygon_perimeter(points)),
+            D2::Color(_, shape) | D2::Rotate(_, shape) | D2::Translate(_, shape) | D2::Mirror(_, shape) => shape.perimeter(),
+            D2::Scale(X(s, _), shape) => shape.perimeter().map(|p| p * s.abs()),
+            D2::Scale2(XY(x, y), shape) if x.abs() == y.abs() => shape.perimeter().map(|p| p * x.abs()),
+            D2::Scale2(_, _) | D2::Join(_, _) | D2::Difference(_, _) => Some(self.regions().iter().map(Region::perimeter).sum()),
+        }
+    }
+
+    /// Center of mass of the profile, exact where `area` is. `None` when the profile is empty.
+    pub fn centroid(&self) -> Option<XY> {
+        match self {
+            D2::Circle(_) => Some(v2(0, 0)),
+            D2::Square(X(s, _)) => Some(v2(*s, *s) * 0.5),
+            D2::Rectangle(xy) => Some(*xy * 0.5),
+            D2::Polygon(points) => Some(polygon_centroid(points)),
+            D2::Color(_, shape) => shape.centroid(),
+            D2::Rotate(X(theta, _), shape) => shape.centroid().map(|c| c.rotate(*theta)),
+            D2::Translate(xy, shape) => shape.centroid().map(|c| c + *xy),
+            D2::Mirror(n, shape) => shape.centroid().map(|c| reflect(c, *n)),
+            D2::Scale(X(s, _), shape) => shape.centroid().map(|c| c * *s),
+            D2::Scale2(XY(x, y), shape) => shape.centroid().map(|c| XY(c.0 * x, c.1 * y)),
+            D2::Join(_, _) | D2::Difference(_, _) => {
+                let regions = self.regions();
+                let area: f32 = regions.iter().map(Region::area).sum();
+                let moment = regions.iter().fold(v2(0, 0), |m, region| m + region.centroid() * region.area());
+                Some(moment * (1. / area)).filter(|_| area > 0.)
+            },
+        }
+    }
+
+    /// Whether every polygon in the tree is simple, see `polygon_is_simple`.
+    pub fn is_simple(&self) -> bool {
+        match self {
+            D2::Polygon(points) => polygon_is_simple(points),
+            shape => shape.children().iter().all(|child| child.is_simple()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4 * b.abs().max(1.), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_polygon() {
+        let triangle = vec![v2(0, 0), v2(4, 0), v2(0, 3)];
+        assert_eq!(polygon_area(&triangle), 6.);
+        assert_eq!(polygon_perimeter(&triangle), 12.);
+        assert_eq!(polygon_centroid(&triangle), v2(4. / 3., 1.));
+        assert!(polygon_is_simple(&triangle));
+        assert!(!polygon_is_simple(&[v2(0, 0), v2(2, 2), v2(2, 0), v2(0, 2)]));
+        assert!(!polygon_is_simple(&[v2(0, 0), v2(2, 0), v2(1, 0)]));
+        assert!(!polygon_is_simple(&[v2(0, 0), v2(4, 0), v2(4, 4), v2(2, 0), v2(0, 4)]));
+    }
+
+    #[test]
+    fn test_d2_measurements() {
+        let shape = D2::circle(2).scale(2).translate(v2(-4, 0));
+        assert_near(shape.area().unwrap(), 4. * PI.0);
+        assert_near(shape.perimeter().unwrap(), 4. * PI.0);
+        assert_eq!(shape.centroid(), Some(v2(-4, 0)));
+        let overlap = D2::square(2).add(D2::square(2).translate(v2(1, 1)));
+        assert_near(overlap.area().unwrap(), 7.);
+        assert_near(overlap.perimeter().unwrap(), 12.);
+        assert_near(overlap.centroid().unwrap().0, 1.5);
+        let rotated = D2::square(2).rotate(90).centroid().unwrap();
+        assert_near(rotated.0, -1.);
+        assert_near(rotated.1, 1.);
+        assert_near(D2::square(2).difference(D2::square(1)).area().unwrap(), 3.);
+    }
+
+    #[test]
+    fn test_extruded_volume() {
+        assert_near(D2::square(2).linear_extrude(3).volume().unwrap(), 12.);
+        assert_near(D2::circle(2).translate(v2(5, 0)).rotate_extrude(360).volume().unwrap(), 10. * PI.0 * PI.0);
+        assert!(!D2::Polygon(Box::new(vec![v2(0, 0), v2(2, 2), v2(2, 0), v2(0, 2)])).translate(v2(1, 1)).is_simple());
+        assert!(g4g_logo(1.).is_simple());
+        assert!(invertible_heart(X(10., None)).area().unwrap() > 0.);
+    }
+}
@@ -0,0 +1,188 @@
+//! Native evaluation of `D2` trees into polygons with holes.
+
+use crate::*;
+use geo::{AffineOps, AffineTransform, BooleanOps, ConvexHull, CoordsIter, IsConvex, Orient, TriangulateEarcut};
+use geo::{Coord, LineString, MultiPoint, MultiPolygon, Polygon};
+use geo::orient::Direction;
+
+/// Number of segments OpenSCAD uses for a circle of radius `r` with the default `$fa = 12` and `$fs = 2`.
+pub fn fragments(r: f32) -> usize {
+    if r < 1e-5 {
+        3
+    } else {
+        (360_f32 / 12.).min(r * 2. * PI.0 / 2.).max(5.).ceil() as usize
+    }
+}
+
+/// A polygon with holes from evaluating a `D2`, its outline counterclockwise and its holes clockwise.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Region {
+    pub outline: Vec<XY>,
+    pub holes: Vec<Vec<XY>>,
+}
+
+fn ring(points: &LineString<f64>) -> Vec<XY> {
+    let mut ring: Vec<XY> = points.coords().map(|c| XY(c.x as f32, c.y as f32)).collect();
+    ring.pop();
+    ring
+}
+
+fn line_string(points: &[XY]) -> LineString<f64> {
+    points.iter().map(|p| Coord { x: p.0 as f64, y: p.1 as f64 }).collect()
+}
+
+impl Region {
+    fn rings(&self) -> impl Iterator<Item = &Vec<XY>> {
+        std::iter::once(&self.outline).chain(self.holes.iter())
+    }
+
+    pub fn area(&self) -> f32 {
+        self.rings().map(|ring| polygon_area(ring)).sum()
+    }
+
+    pub fn perimeter(&self) -> f32 {
+        self.rings().map(|ring| polygon_perimeter(ring)).sum()
+    }
+
+    pub fn centroid(&self) -> XY {
+        let moment = self.rings().fold(v2(0, 0), |m, ring| m + polygon_centroid(ring) * polygon_area(ring));
+        moment * (1. / self.area())
+    }
+
+    /// The region as a SCAD polygon, with its holes cut out.
+    pub fn polygon(&self) -> D2 {
+        self.holes.iter().fold(D2::Polygon(Box::new(self.outline.clone())), |shape, hole| {
+            shape.difference(D2::Polygon(Box::new(hole.clone())))
+        })
+    }
+}
+
+/// Union of `shapes`, merged pairwise to keep the operands balanced.
+fn union_all(mut shapes: Vec<MultiPolygon<f64>>) -> MultiPolygon<f64> {
+    while shapes.len() > 1 {
+        shapes = shapes.chunks(2).map(|pair| match pair {
+            [a, b] => a.union(b),
+            [a] => a.clone(),
+            _ => unreachable!(),
+        }).collect();
+    }
+    shapes.pop().unwrap_or_else(|| MultiPolygon::new(vec![]))
+}
+
+fn hull(shapes: &[MultiPolygon<f64>]) -> MultiPolygon<f64> {
+    let points: MultiPoint<f64> = shapes.iter().flat_map(|shape| shape.coords_iter()).collect::<Vec<_>>().into();
+    if points.0.len() < 3 {
+        return MultiPolygon::new(vec![]);
+    }
+    MultiPolygon::new(vec![points.convex_hull()])
+}
+
+/// `shape` split into convex polygons, triangulating those that are not convex.
+fn convex_pieces(shape: &MultiPolygon<f64>) -> Vec<Polygon<f64>> {
+    shape.iter().flat_map(|polygon| {
+        if polygon.interiors().is_empty() && polygon.exterior().is_convex() {
+            vec![polygon.clone()]
+        } else {
+            polygon.earcut_triangles().into_iter().map(|t| t.to_polygon()).collect()
+        }
+    }).collect()
+}
+
+/// Minkowski sum as the union of the hulls of sums of convex pieces.
+fn minkowski(a: &MultiPolygon<f64>, b: &MultiPolygon<f64>) -> MultiPolygon<f64> {
+    let (a, b) = (convex_pieces(a), convex_pieces(b));
+    union_all(a.iter().flat_map(|pa| b.iter().map(move |pb| {
+        let sums: Vec<Coord<f64>> = pa.exterior().coords()
+            .flat_map(|ca| pb.exterior().coords().map(move |cb| *ca + *cb))
+            .collect();
+        MultiPolygon::new(vec![MultiPoint::from(sums).convex_hull()])
+    })).collect())
+}
+
+impl D2 {
+    /// Evaluate the tree like OpenSCAD into polygons with holes, using OpenSCAD's circle segments.
+    pub fn regions(&self) -> Vec<Region> {
+        self.evaluate().orient(Direction::Default).iter().map(|polygon| Region {
+            outline: ring(polygon.exterior()),
+            holes: polygon.interiors().iter().map(ring).collect(),
+        }).collect()
+    }
+
+    fn evaluate(&self) -> MultiPolygon<f64> {
+        let transformed = |shape: &D2, t: AffineTransform<f64>| shape.evaluate().affine_transform(&t);
+        match self {
+            D2::Circle(X(d, _)) => {
+                let n = fragments(d / 2.);
+                let points: Vec<XY> = (0..n).map(|ii| XY(d / 2., 0.).rotate(360. * ii as f32 / n as f32)).collect();
+                Polygon::new(line_string(&points), vec![]).into()
+            },
+            D2::Square(X(s, _)) => Polygon::new(line_string(&[v2(0, 0), v2(*s, 0.), v2(*s, *s), v2(0., *s)]), vec![]).into(),
+            D2::Rectangle(XY(x, y)) => Polygon::new(line_string(&[v2(0, 0), v2(*x, 0.), v2(*x, *y), v2(0., *y)]), vec![]).into(),
+            D2::Polygon(points) => Polygon::new(line_string(points), vec![]).into(),
+            D2::Color(_, shape) => shape.evaluate(),
+            D2::Rotate(X(theta, _), shape) => transformed(shape, AffineTransform::rotate(*theta as f64, Coord::zero())),
+            D2::Translate(XY(x, y), shape) => transformed(shape, AffineTransform::translate(*x as f64, *y as f64)),
+            D2::Scale(X(s, _), shape) => transformed(shape, AffineTransform::scale(*s as f64, *s as f64, Coord::zero())),
+            D2::Scale2(XY(x, y), shape) => transformed(shape, AffineTransform::scale(*x as f64, *y as f64, Coord::zero())),
+            D2::Mirror(XY(x, y), shape) => {
+                let (x, y) = (*x as f64, *y as f64);
+                let k = 2. / (x * x + y * y);
+                transformed(shape, AffineTransform::new(1. - k * x * x, -k * x * y, 0., -k * x * y, 1. - k * y * y, 0.))
+            },
+            D2::Difference(a, b) => a.evaluate().difference(&b.evaluate()),
+            D2::Join(op, v) => {
+                let shapes: Vec<MultiPolygon<f64>> = v.iter().map(|x| x.evaluate()).collect();
+                match op {
+                    BoolOp::Union => union_all(shapes),
+                    BoolOp::Hull => hull(&shapes),
+                    BoolOp::Intersection => shapes.into_iter().reduce(|a, b| a.intersection(&b))
+                        .unwrap_or_else(|| MultiPolygon::new(vec![])),
+                    BoolOp::Minkowski => shapes.into_iter().reduce(|a, b| minkowski(&a, &b))
+                        .unwrap_or_else(|| MultiPolygon::new(vec![])),
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4 * b.abs().max(1.), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_booleans() {
+        let frame = D2::square(4).difference(D2::square(2).translate(v2(1, 1)));
+        let regions = frame.regions();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].holes.len(), 1);
+        assert_near(regions[0].area(), 12.);
+        assert_near(regions[0].perimeter(), 24.);
+        let apart = D2::square(1).add(D2::square(1).translate(v2(3, 0))).regions();
+        assert_eq!(apart.len(), 2);
+        assert_near(D2::square(2).intersection(D2::square(2).translate(v2(1, 1))).regions()[0].area(), 1.);
+    }
+
+    #[test]
+    fn test_hull_minkowski_mirror() {
+        let hull = D2::square(1).add(D2::square(1).translate(v2(3, 0))).hull().regions();
+        assert_near(hull[0].area(), 4.);
+        let rounded = D2::Rectangle(v2(4, 2)).minkowski(D2::square(1).translate(v2(-0.5, -0.5))).regions();
+        assert_near(rounded[0].area(), 15.);
+        let ell = D2::Polygon(Box::new(vec![v2(0, 0), v2(2, 0), v2(2, 1), v2(1, 1), v2(1, 2), v2(0, 2)]));
+        assert_near(ell.clone().minkowski(D2::square(1)).regions()[0].area(), 8.);
+        let mirrored = ell.mirror(v2(1, 0)).regions();
+        assert_near(mirrored[0].centroid().0, -polygon_centroid(&[v2(0, 0), v2(2, 0), v2(2, 1), v2(1, 1), v2(1, 2), v2(0, 2)]).0);
+        assert!(polygon_area(&mirrored[0].outline) > 0.);
+    }
+
+    #[test]
+    fn test_circle_fragments() {
+        assert_eq!(fragments(1.), 5);
+        assert_eq!(fragments(100.), 30);
+        assert_eq!(D2::circle(200).regions()[0].outline.len(), 30);
+    }
+}
@@ -0,0 +1,182 @@
+//! Writing models to an output directory and rendering them with a local OpenSCAD.
+
+use anyhow::{anyhow, bail, Result};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// One file produced for a model and how long it took.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub file: PathBuf,
+    pub seconds: f64,
+    pub error: Option<String>,
+}
+
+impl Step {
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "file": self.file.to_string_lossy(),
+            "seconds": self.seconds,
+            "ok": self.error.is_none(),
+            "error": self.error,
+        })
+    }
+}
+
+/// Writes `<model>.scad` files into `out_dir` and, with `openscad` set, renders each `format` from them.
+#[derive(Clone, Debug)]
+pub struct Renderer {
+    pub out_dir: PathBuf,
+    pub openscad: Option<PathBuf>,
+    pub formats: Vec<String>,
+    /// Longest a model binary or an OpenSCAD render may run before it is killed.
+    pub timeout: Option<Duration>,
+    /// Steps done for each model, in order.
+    pub models: Vec<(String, Vec<Step>)>,
+}
+
+impl Renderer {
+    /// Renderer using the `openscad` found on `PATH`, if any, to produce STL and PNG files,
+    /// giving up on anything that takes longer than ten minutes.
+    pub fn new<P: AsRef<Path>>(out_dir: P) -> Renderer {
+        Renderer {
+            out_dir: out_dir.as_ref().to_path_buf(),
+            openscad: find_executable("openscad"),
+            formats: vec!["stl".to_string(), "png".to_string()],
+            timeout: Some(Duration::from_secs(600)),
+            models: vec![],
+        }
+    }
+
+    /// Time `f` producing `file`, recording a failure instead of stopping.
+    fn step<F: FnOnce() -> Result<()>>(file: PathBuf, f: F) -> Step {
+        let start = Instant::now();
+        let error = f().err().map(|e| e.to_string());
+        Step { file, seconds: start.elapsed().as_secs_f64(), error }
+    }
+
+    /// Record a model that failed before it had any SCAD.
+    pub fn failed(&mut self, name: &str, seconds: f64, error: String) {
+        let step = Step { file: self.out_dir.join(format!("{}.scad", name)), seconds, error: Some(error) };
+        self.models.push((name.to_string(), vec![step]));
+    }
+
+    /// Write the SCAD of model `name` and render it. `seconds` is the time spent generating `scad`.
+    pub fn render(&mut self, name: &str, scad: &str, seconds: f64) -> &[Step] {
+        let scad_file = self.out_dir.join(format!("{}.scad", name));
+        let mut step = Renderer::step(scad_file.clone(), || {
+            std::fs::create_dir_all(&self.out_dir)?;
+            Ok(std::fs::write(&scad_file, scad)?)
+        });
+        step.seconds += seconds;
+        let mut steps = vec![step];
+        if let (Some(openscad), None) = (&self.openscad, &steps[0].error) {
+            for format in &self.formats {
+                let file = self.out_dir.join(format!("{}.{}", name, format));
+                steps.push(Renderer::step(file.clone(), || {
+                    let output = output_within(Command::new(openscad).arg("-o").arg(&file).arg(&scad_file), self.timeout)?;
+                    if !output.status.success() {
+                        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+                    }
+                    Ok(())
+                }));
+            }
+        }
+        self.models.push((name.to_string(), steps));
+        &self.models.last().unwrap().1
+    }
+
+    /// JSON description of everything built and how long each step took.
+    pub fn manifest(&self) -> serde_json::Value {
+        serde_json::json!({
+            "openscad": self.openscad.as_ref().map(|p| p.to_string_lossy()),
+            "models": self.models.iter().map(|(name, steps)| serde_json::json!({
+                "name": name,
+                "seconds": steps.iter().map(|s| s.seconds).sum::<f64>(),
+                "steps": steps.iter().map(|s| s.json()).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Write `manifest.json` into the output directory.
+    pub fn write_manifest(&self) -> Result<PathBuf> {
+        let path = self.out_dir.join("manifest.json");
+        std::fs::create_dir_all(&self.out_dir)?;
+        std::fs::write(&path, serde_json::to_string_pretty(&self.manifest())?)?;
+        Ok(path)
+    }
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<std::io::Result<Vec<u8>>> {
+    std::thread::spawn(move || {
+        let mut bytes = vec![];
+        if let Some(mut pipe) = pipe {
+            pipe.read_to_end(&mut bytes)?;
+        }
+        Ok(bytes)
+    })
+}
+
+/// Run `command` and collect its output like `Command::output`, killing it and failing after `timeout`.
+pub fn output_within(command: &mut Command, timeout: Option<Duration>) -> Result<Output> {
+    let mut child = command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    let (stdout, stderr) = (read_pipe(child.stdout.take()), read_pipe(child.stderr.take()));
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if let Some(timeout) = timeout.filter(|t| start.elapsed() > *t) {
+            child.kill()?;
+            child.wait()?;
+            bail!("timed out after {:.1}s", timeout.as_secs_f64());
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    let join = |pipe: JoinHandle<std::io::Result<Vec<u8>>>| pipe.join().map_err(|_| anyhow!("reading output panicked"));
+    Ok(Output { status, stdout: join(stdout)??, stderr: join(stderr)?? })
+}
+
+/// First executable called `name` on `PATH`.
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let exe = format!("{}{}", name, std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(&exe))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let dir = std::env::temp_dir().join(format!("flowscad_render_{}", std::process::id()));
+        let mut renderer = Renderer::new(&dir);
+        renderer.openscad = None;
+        renderer.render("cube", "cube(size = 1);", 0.5);
+        renderer.failed("broken", 0.1, "exit status 101".to_string());
+        assert_eq!(std::fs::read_to_string(dir.join("cube.scad")).unwrap(), "cube(size = 1);");
+        let manifest = renderer.manifest();
+        assert_eq!(manifest["models"][0]["name"], "cube");
+        assert_eq!(manifest["models"][0]["steps"][0]["ok"], true);
+        assert!(manifest["models"][0]["seconds"].as_f64().unwrap() >= 0.5);
+        assert_eq!(manifest["models"][1]["steps"][0]["error"], "exit status 101");
+        assert!(renderer.write_manifest().unwrap().is_file());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_within() {
+        let output = output_within(Command::new("sh").args(["-c", "echo cube"]), Some(Duration::from_secs(10))).unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "cube\n");
+        let start = Instant::now();
+        let error = output_within(Command::new("sleep").arg("10"), Some(Duration::from_millis(100))).unwrap_err();
+        assert_eq!(error.to_string(), "timed out after 0.1s");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -5,28 +5,6 @@ use crate::*;
 
 pub const MAX2: f32 = 1000.;
 
-impl<T: Iterator<Item=D2>> DIterator<D2> for T {
-    fn hull(self: Self) -> D2 {
-        // D2::Hull(Box::new(self.collect::<Vec<D2>>()))
-        D2::Join("hull", Box::new(self.collect::<Vec<D2>>()))
-    }
-
-    fn union(self: Self) -> D2 {
-        // D2::Union(Box::new(self.collect::<Vec<D2>>()))
-        D2::Join("union", Box::new(self.collect::<Vec<D2>>()))
-    }
-
-    fn intersection(self: Self) -> D2 {
-        // D2::Intersection(Box::new(self.collect::<Vec<D2>>()))
-        D2::Join("intersection", Box::new(self.collect::<Vec<D2>>()))
-    }
-
-    fn minkowski(self: Self) -> D2 {
-        // D2::Minkowski(Box::new(self.collect::<Vec<D2>>()))
-        D2::Join("minkowski", Box::new(self.collect::<Vec<D2>>()))
-    }
-}
-
 impl std::ops::Add<D2> for D2 {
     type Output = D2;
 
@@ -43,16 +21,70 @@ impl std::ops::Sub<D2> for D2 {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Aim {
     N, S, E, W,
     U, D, 
     // L, R,
-    // Angle(X),
+    /// Direction in the XY plane `X` degrees counterclockwise from `E`.
+    Angle(X),
+    /// Arbitrary direction in space.
+    Vector(XYZ),
+}
+
+impl Aim {
+    /// Unit vector pointing in the direction of `self`.
+    /// Panics for a zero `Vector`, which has no direction.
+    pub fn xyz(&self) -> XYZ {
+        match self {
+            Aim::N => v3(0, 1, 0),
+            Aim::S => v3(0, -1, 0),
+            Aim::E => v3(1, 0, 0),
+            Aim::W => v3(-1, 0, 0),
+            Aim::U => v3(0, 0, 1),
+            Aim::D => v3(0, 0, -1),
+            Aim::Angle(X(theta, _)) => v3(theta.to_radians().cos(), theta.to_radians().sin(), 0),
+            Aim::Vector(xyz) => {
+                assert!(xyz.norm() > 0., "Aim::Vector needs a nonzero direction");
+                *xyz * (1. / xyz.norm())
+            },
+        }
+    }
+
+    /// Degrees counterclockwise from `E` in the XY plane, with `U` and `D` drawn as `N` and `S`.
+    /// Panics for a `Vector` along Z, which has no direction in the plane.
+    pub fn angle(&self) -> X {
+        match self {
+            Aim::E => X(0., None),
+            Aim::N | Aim::U => X(90., None),
+            Aim::W => X(180., None),
+            Aim::S | Aim::D => X(270., None),
+            Aim::Angle(theta) => theta.clone(),
+            Aim::Vector(XYZ(x, y, _)) => {
+                assert!(x.hypot(*y) > 0., "Aim::Vector needs a nonzero direction in the XY plane");
+                X(y.atan2(*x).to_degrees(), None)
+            },
+        }
+    }
+
+    /// The direction pointing the other way.
+    pub fn opposite(&self) -> Aim {
+        match self {
+            Aim::N => Aim::S,
+            Aim::S => Aim::N,
+            Aim::E => Aim::W,
+            Aim::W => Aim::E,
+            Aim::U => Aim::D,
+            Aim::D => Aim::U,
+            Aim::Angle(theta) => Aim::Angle(theta.clone() + 180),
+            Aim::Vector(xyz) => Aim::Vector(-*xyz),
+        }
+    }
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum D2 {
     Circle(X),
     Square(X),
@@ -68,7 +100,7 @@ pub enum D2 {
     // Intersection(Box<Vec<D2>>),
     // Union(Box<Vec<D2>>),
     // Minkowski(Box<Vec<D2>>),
-    Join(&'static str, Box<Vec<D2>>),
+    Join(BoolOp, Box<Vec<D2>>),
     Difference(Box<D2>, Box<D2>),
 }
 
@@ -88,14 +120,7 @@ impl D2 {
 
 
     pub fn add(self, other: D2) -> D2 {
-        match self { // Combine Unions if possible
-            D2::Join("union", vec) => {
-                let mut vec = vec;
-                vec.push(other);
-                D2::Join("union", vec)
-                },
-            _ => D2::Join("union", Box::new(vec![self, other])),
-        }
+        self.combine(BoolOp::Union, other)
     }
 
     pub fn difference(self, other: D2) -> D2 {
@@ -110,50 +135,48 @@ impl D2 {
         D2::intersection(self, other)
     }
 
+    /// Square of side `MAX2` covering the side of the origin in direction `aim`, the same size for
+    /// every direction since OpenSCAD struggles with huge coordinates. Use `cut` for shapes reaching
+    /// further than `MAX2/2`. Panics for an `Aim::Vector` along Z.
     pub fn half_plane(aim: Aim) -> D2 {
         match aim {
             Aim::N => D2::square(MAX2).translate(v2(-MAX2/2., 0.)),
-            Aim::S => D2::square(MAX).translate(v2(-MAX/2., -MAX)),
-            Aim::E => D2::square(MAX).translate(v2(0., -MAX/2.)),
-            Aim::W => D2::square(MAX).translate(v2(-MAX, -MAX/2.)),
-            Aim::U => D2::square(MAX).translate(v2(-MAX/2., 0.)),
-            Aim::D => D2::square(MAX).translate(v2(-MAX/2., -MAX)),
-            // Aim::Angle(theta) => D2::Square(StrictlyPositiveFinite(MAX)).translate(XY(0., -MAX/2.)).rotate(*theta),
-            // Aim::Angle(theta) => D2::HalfPlane(Aim::E).rotate(*theta),
+            Aim::S => D2::square(MAX2).translate(v2(-MAX2/2., -MAX2)),
+            Aim::E => D2::square(MAX2).translate(v2(0., -MAX2/2.)),
+            Aim::W => D2::square(MAX2).translate(v2(-MAX2, -MAX2/2.)),
+            Aim::U => D2::square(MAX2).translate(v2(-MAX2/2., 0.)),
+            Aim::D => D2::square(MAX2).translate(v2(-MAX2/2., -MAX2)),
+            Aim::Angle(_) | Aim::Vector(_) => D2::half_plane_within(aim, MAX2/2.),
             }
     }
 
-    pub fn add_map<F>(self, f: F) -> D2 where F: Fn(D2) -> D2 {
-        self.clone().add(f(self))
+    /// Square covering the side of the origin in direction `aim` up to distance `reach`.
+    pub fn half_plane_within<T: Into<X>>(aim: Aim, reach: T) -> D2 {
+        let r = reach.into().0;
+        let theta = aim.angle();
+        let square = D2::square(2. * r).translate(v2(0., -r));
+        if theta.0 == 0. { square } else { square.rotate(theta) }
+    }
+
+    /// Keep the part of the shape on the side of the origin in direction `aim`.
+    /// The cutting square is sized from the bounds of the shape, like `D3::cut`.
+    pub fn cut(self, aim: Aim) -> D2 {
+        let bounds = self.bounds();
+        if bounds.is_empty() { return self; }
+        let reach = bounds.corners().iter().map(|c| c.norm()).fold(0., f32::max) + 1.;
+        self.intersection(D2::half_plane_within(aim, reach))
     }
 
     pub fn hull(self) -> D2 {
-        match self { // Combine Unions if possible
-            D2::Join("union", vec) => D2::Join("hull", vec),
-            _ => D2::Join("hull", Box::new(vec![self])),
-        }
+        self.hull_of()
     }
 
     pub fn intersection(self, other: D2) -> D2 {
-        match self { // Combine intersections if possible
-            D2::Join("intersection", vec) => {
-                let mut vec = vec;
-                vec.push(other);
-                D2::Join("intersection", vec)
-                },
-            _ => D2::Join("intersection", Box::new(vec![self, other])),
-        }
+        self.combine(BoolOp::Intersection, other)
     }
 
     pub fn minkowski(self, other: D2) -> D2 {
-        match self { // Combine Minkowski sums if possible
-            D2::Join("minkowski", vec) => {
-                let mut vec = vec;
-                vec.push(other);
-                D2::Join("minkowski", vec)
-                },
-            _ => D2::Join("minkowski", Box::new(vec![self, other])),
-        }
+        self.combine(BoolOp::Minkowski, other)
     }
 
     pub fn triangle(xy0: XY, xy1: XY, xy2: XY) -> D2 {
@@ -172,49 +195,41 @@ impl D2 {
         D2::Polygon(Box::new(points.iter().map(|xy| xy.clone().into()).collect()))
     }
 
-    pub fn translate<IXY: Into<XY>>(&self, xy: IXY) -> D2 {
-        // TODO: Is clone needed here?
-        match self {
-            D2::Translate(v, d2) => D2::Translate(*v+xy.into(), d2.clone()),
-            _ => D2::Translate(xy.into(), Box::new(self.clone())),
+    pub fn translate<IXY: Into<XY>>(self, xy: IXY) -> D2 {
+        match self { // Combine translations if possible
+            D2::Translate(v, d2) => D2::Translate(v + xy.into(), d2),
+            _ => D2::Translate(xy.into(), Box::new(self)),
         }
     }
 
-    pub fn mirror(&self, xy: XY) -> D2 {
-        D2::Mirror(xy, Box::new(self.clone()))
-    }
-
-    pub fn iter_translate<'a>(&'a self, xy: XY, n: u32) -> impl Iterator<Item = D2> + 'a {
-        (0..n).map(move |ii| self.translate(v2(xy.0 * ii as f32, xy.1 * ii as f32)))
+    pub fn mirror(self, xy: XY) -> D2 {
+        D2::Mirror(xy, Box::new(self))
     }
 
-    pub fn rotate<IX: Into<X>>(&self, theta: IX) -> D2 {
-        match self {
-            D2::Rotate(phi, d2) => D2::Rotate(*phi + theta.into(), d2.clone()),
-            _ => D2::Rotate(theta.into(), Box::new(self.clone())),
+    pub fn rotate<IX: Into<X>>(self, theta: IX) -> D2 {
+        match self { // Combine rotations if possible
+            D2::Rotate(phi, d2) => D2::Rotate(phi + theta.into(), d2),
+            _ => D2::Rotate(theta.into(), Box::new(self)),
         }
     }
 
     pub fn iter_rotate<'a, IX: Into<X>>(&'a self, theta: IX, n: u32) -> impl Iterator<Item = D2> + 'a {
-        let angle = theta.into();
-        // (0..n).map(move |ii| self.rotate(theta.into() * <u32 as Into<X>>::ii.into()))
-        (0..n).map(move |ii| self.rotate(angle * ii as f32))
+        Shape::iter_rotate(self, theta.into(), n)
     }
 
     pub fn iter_rotate_equal<'a>(&'a self, n: u32) -> impl Iterator<Item = D2> + 'a {
-        (0..n).map(move |ii| self.rotate(360./(n as f64) * ii as f64))
+        (0..n).map(move |ii| self.clone().rotate(360./(n as f64) * ii as f64))
     }
 
     pub fn iter_square_edge<'a, D: Into<X>>(&'a self, d: D) -> impl Iterator<Item = D2> + 'a {
-        let shift = d.into();
+        let shift = d.into().0;
         vec![v2(shift, 0.), v2(0., shift), v2(-shift, 0.), v2(0., -shift)]
             .into_iter()
-            .map(move |xy| self.translate(xy))
+            .map(move |xy| self.clone().translate(xy))
     }
 
     pub fn translate_vec<IXY: Into<XY> + Clone>(&self, ixy: IXY, n: u32) -> Vec<D2> {
-        let xy: XY = ixy.into();
-        (0..n).map(move |ii| self.translate(v2(xy.0 * ii as f32, xy.1 * ii as f32))).collect::<Vec<_>>()
+        self.iter_translate(ixy.into(), n).collect::<Vec<_>>()
     }
 
     pub fn color(self, color_name: ColorEnum) -> D2 {
@@ -223,7 +238,7 @@ impl D2 {
 
     /// Scale size by the factor `s`.
     pub fn scale<T: Into<X>>(self, scale_factor: T) -> D2 {
-        D2::Scale(scale_factor.into(), Box::new(self.clone()))
+        D2::Scale(scale_factor.into(), Box::new(self))
     }
 
     /// Scale in `x` and `y` directions.
@@ -245,7 +260,7 @@ impl std::iter::Sum for D2 {
       where 
         I: Iterator<Item = Self>
     {
-        D2::Join("union", Box::new(iter.collect::<Vec<Self>>()))
+        D2::join(BoolOp::Union, iter.collect())
     }
 }
 
@@ -254,7 +269,7 @@ impl std::iter::Product for D2 {
       where 
         I: Iterator<Item = Self>
     {
-        D2::Join("intersection", Box::new(iter.collect::<Vec<Self>>()))
+        D2::join(BoolOp::Intersection, iter.collect())
     }
 }
 
@@ -281,7 +296,7 @@ impl SCAD for D2 {
             D2::Translate(XY(x,y), shape) => format!("translate(v = [{}, {}]) {{\n  {}\n}}", x, y, indent(shape)),
             D2::Mirror(XY(x,y), shape) => format!("mirror(v = [{}, {}]) {{\n  {}\n}}", x, y, indent(shape)),
             // D2::Mirror(XY(x,y), shape) => format!("mirror(v = [{}, {}]) {{\n  {}\n}}", x, y, indent(shape)),
-            D2::Rotate(X(theta), shape) => format!("rotate({}) {{\n  {}\n}}", theta, indent(shape)),
+            D2::Rotate(theta, shape) => format!("rotate({}) {{\n  {}\n}}", theta, indent(shape)),
             D2::Scale(s, shape) => format!("scale(v = {}) {{\n  {}\n}}", s, indent(shape)),
             D2::Scale2(XY(x,y), shape) => format!("scale(v = [{}, {}]) {{\n  {}\n}}", x, y, indent(shape)),
             // D2::Union(v) => format!( "union() {{\n  {}\n}}",
@@ -292,8 +307,7 @@ impl SCAD for D2 {
                 // v.iter().map(|x| format!("{}", indent(x))).collect::<Vec<_>>().join("\n  ")),
             // D2::Minkowski(v) => format!("minkowski() {{\n  {}\n}}",
                 // v.iter().map(|x| format!("{}", indent(x))).collect::<Vec<_>>().join("\n  ")),
-            D2::Join(name, v) => format!("{}() {{\n  {}\n}}", &name,
-                v.iter().map(|x| format!("{}", indent(x))).collect::<Vec<_>>().join("\n  ")),
+            D2::Join(op, v) => scad_join(*op, v),
             D2::Difference(shape1, shape2) => format!("difference() {{\n  {}\n  {}\n}}", indent(shape1), indent(shape2)),
         }
     }
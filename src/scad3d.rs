@@ -1,29 +1,8 @@
 
 use crate::*;
 
-pub const MAX2: f32 = 1000.;
-
-impl<T: Iterator<Item=D3>> DIterator<D3> for T {
-    fn hull(self: Self) -> D3 {
-        D3::Hull(Box::new(self.collect::<Vec<D3>>()))
-        // D2::Join("hull", Box::new(self.collect::<Vec<D2>>()))
-    }
-
-    fn union(self: Self) -> D3 {
-        D3::Union(Box::new(self.collect::<Vec<D3>>()))
-    }
-
-    fn intersection(self: Self) -> D3 {
-        D3::Intersection(Box::new(self.collect::<Vec<D3>>()))
-    }
-
-    fn minkowski(self: Self) -> D3 {
-        D3::Minkowski(Box::new(self.collect::<Vec<D3>>()))
-    }
-}
-
-
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum D3 {
     Cube(X),
     Cuboid(XYZ),
@@ -35,15 +14,13 @@ pub enum D3 {
     Scale(X, Box<D3>),
     Scale3(XYZ, Box<D3>),
     Rotate(XYZ, Box<D3>),
+    Mirror(XYZ, Box<D3>),
     LinearExtrude(X, Box<D2>),
     RotateExtrude(X, Box<D2>),
-    Hull(Box<Vec<D3>>),
-    Intersection(Box<Vec<D3>>),
-    Union(Box<Vec<D3>>),
-    Minkowski(Box<Vec<D3>>),
     Difference(Box<D3>, Box<D3>),
-    Join(&'static str, Box<Vec<D3>>),
-    // TODO: Join(&'static str, Box<Vec<D3>>),
+    Join(BoolOp, Box<Vec<D3>>),
+    Animate(Keyframes, Box<D3>),
+    Named(Meta, Box<D3>),
 }
 
 
@@ -62,8 +39,7 @@ impl std::iter::Sum for D3 {
       where 
         I: Iterator<Item = Self>
     {
-        D3::Union(Box::new(iter.collect::<Vec<Self>>()))
-        // D3::Join("union", Box::new(iter.collect::<Vec<Self>>()))
+        D3::join(BoolOp::Union, iter.collect())
     }
 }
 
@@ -72,7 +48,7 @@ impl std::iter::Product for D3 {
       where 
         I: Iterator<Item = Self>
     {
-        D3::Intersection(Box::new(iter.collect::<Vec<Self>>()))
+        D3::join(BoolOp::Intersection, iter.collect())
     }
 }
 
@@ -84,8 +60,8 @@ impl std::iter::Product for D3 {
 impl SCAD for D3 {
     fn scad(&self) -> String {
         match &self {
-            D3::LinearExtrude(X(h), shape) => format!("linear_extrude(height = {}) {{\n  {}\n}}", h, indent(shape)),
-            D3::RotateExtrude(X(angle), shape) => format!("rotate_extrude(angle = {}) {{\n  {}\n}}", angle, indent(shape)),
+            D3::LinearExtrude(h, shape) => format!("linear_extrude(height = {}) {{\n  {}\n}}", h, indent(shape)),
+            D3::RotateExtrude(angle, shape) => format!("rotate_extrude(angle = {}) {{\n  {}\n}}", angle, indent(shape)),
             D3::Cube(size) => format!("cube(size = {});", size),
             D3::Cuboid(xyz) => format!("cube(size = [{}, {}, {}]);", xyz.0, xyz.1, xyz.2),
             D3::Sphere(radius) => format!("sphere(r = {});", radius),
@@ -100,19 +76,14 @@ impl SCAD for D3 {
                 , shape.indent()),
             D3::Scale(s, shape) => format!("scale(v = {}) {{\n  {}\n}}", s, shape.indent()),
             D3::Scale3(v, shape) => format!("scale(v = [{}, {}, {}]) {{\n  {}\n}}", v.0, v.1, v.2, shape.indent()),
-            D3::Union(v) => format!( "union() {{\n  {}\n}}",
-                v.iter().map(|x| format!("{}", indent_d3(x))).collect::<Vec<_>>().join("\n  ")),
-            D3::Hull(v) => format!("hull() {{\n  {}\n}}",
-                v.iter().map(|x| format!("{}", indent_d3(x))).collect::<Vec<_>>().join("\n  ")),
-            D3::Intersection(v) => format!("intersection() {{\n  {}\n}}",
-                v.iter().map(|x| format!("{}", indent_d3(x))).collect::<Vec<_>>().join("\n  ")),
-            D3::Minkowski(v) => format!("minkowski() {{\n  {}\n}}",
-                v.iter().map(|x| format!("{}", indent_d3(x))).collect::<Vec<_>>().join("\n  ")),
             D3::Translate(xyz, shape) => format!("translate(v = [{}, {}, {}]) {{\n  {}\n}}", xyz.0, xyz.1, xyz.2, shape.indent()),
             D3::Rotate(xyz, shape) => format!("rotate([{}, {}, {}]) {{\n  {}\n}}", xyz.0, xyz.1, xyz.2, shape.indent()),
+            D3::Mirror(xyz, shape) => format!("mirror(v = [{}, {}, {}]) {{\n  {}\n}}", xyz.0, xyz.1, xyz.2, shape.indent()),
+            D3::Animate(keys @ Keyframes::Translate(_), shape) => format!("translate(v = {}) {{\n  {}\n}}", keys.expression(), shape.indent()),
+            D3::Animate(keys @ Keyframes::Rotate(_), shape) => format!("rotate({}) {{\n  {}\n}}", keys.expression(), shape.indent()),
+            D3::Named(meta, shape) => format!("{}\n{}", meta.comment(), shape.scad()),
             D3::Difference(shape1, shape2) => format!("difference() {{\n  {}\n  {}\n}}", indent_d3(shape1), indent_d3(shape2)),
-            D3::Join(name, v) => format!("{}() {{\n  {}\n}}", &name,
-                v.iter().map(|x| format!("{}", x.indent())).collect::<Vec<_>>().join("\n  ")),
+            D3::Join(op, v) => scad_join(*op, v),
         }
     }
     fn indent(&self) -> String {
@@ -145,14 +116,18 @@ impl D3 {
             )
     }
 
+    /// Cube of edge `MAX2` covering the side of the origin in direction `aim`, like `D2::half_plane`.
+    /// Use `cut` for shapes reaching further than `MAX2/2`.
     pub fn half_space(aim: Aim) -> D3 {
         match aim {
-            Aim::N => D3::cube(MAX).translate(v3(-MAX/2., 0., -MAX/2.)),
-            Aim::S => D3::cube(MAX).translate(v3(-MAX/2., -MAX, -MAX/2.)),
-            Aim::E => D3::cube(MAX).translate(v3(0., -MAX/2., -MAX/2.)),
-            Aim::W => D3::cube(MAX).translate(v3(-MAX, -MAX/2., -MAX/2.)),
-            Aim::U => D3::cube(MAX).translate(v3(-MAX/2., -MAX/2., 0.)),
-            Aim::D => D3::cube(MAX).translate(v3(-MAX/2., -MAX/2., -MAX)),
+            Aim::N => D3::cube(MAX2).translate(v3(-MAX2/2., 0., -MAX2/2.)),
+            Aim::S => D3::cube(MAX2).translate(v3(-MAX2/2., -MAX2, -MAX2/2.)),
+            Aim::E => D3::cube(MAX2).translate(v3(0., -MAX2/2., -MAX2/2.)),
+            Aim::W => D3::cube(MAX2).translate(v3(-MAX2, -MAX2/2., -MAX2/2.)),
+            Aim::U => D3::cube(MAX2).translate(v3(-MAX2/2., -MAX2/2., 0.)),
+            Aim::D => D3::cube(MAX2).translate(v3(-MAX2/2., -MAX2/2., -MAX2)),
+            Aim::Angle(_) => D3::half_space(Aim::Vector(aim.xyz())),
+            Aim::Vector(xyz) => D3::half_space(Aim::U).rotate(xyz.rotation_from_z()),
             }
     }
 
@@ -166,9 +141,53 @@ impl D3 {
             - self
     }
 
+    /// Subtract `self` from its bounding box grown by `margin` on every side.
+    pub fn negative<T: Into<X>>(self, margin: T) -> D3 {
+        self.bounds().pad(margin.into().0).cuboid() - self
+    }
+
+    /// Grow the shape by the Minkowski sum with `tool`, which should be centered at the origin.
+    pub fn grow(self, tool: D3) -> D3 {
+        self.minkowski(tool)
+    }
+
+    /// Shrink the shape by growing its negative space with `tool`, which should be centered at the origin.
+    /// The negative space is sized from the bounds of the shape and the tool.
+    pub fn shrink(self, tool: D3) -> D3 {
+        let XYZ(x0, y0, z0) = tool.bounds().min;
+        let XYZ(x1, y1, z1) = tool.bounds().max;
+        let reach = [x0, y0, z0, x1, y1, z1].iter().fold(0., |r: f32, x| r.max(x.abs()));
+        let margin = 2.0 * reach + 1.0;
+        self.bounds().pad(margin - 0.5).cuboid()
+            - self.negative(margin).minkowski(tool)
+    }
+
+    /// Grow (`delta > 0`) or shrink (`delta < 0`) the shape by `delta` using a sphere.
+    pub fn offset<T: Into<X>>(self, delta: T) -> D3 {
+        let X(d, _) = delta.into();
+        match d {
+            d if d > 0. => self.grow(D3::sphere(d)),
+            d if d < 0. => self.shrink(D3::sphere(-d)),
+            _ => self,
+        }
+    }
+
+    /// Grow (`gap > 0`) or shrink (`gap < 0`) the shape by at least `gap` using a truncated octahedron.
+    /// Flat faces stay flat and OpenSCAD renders it much faster than `offset`.
+    pub fn clearance<T: Into<X>>(self, gap: T) -> D3 {
+        let X(g, _) = gap.into();
+        // Hexagonal faces of a truncated octahedron are sqrt(6)/2 times the edge length from its center.
+        let tool = |g: f32| D3::truncated_octahedron((g * 2.0 / 6.0_f32.sqrt()) as f64);
+        match g {
+            g if g > 0. => self.grow(tool(g)),
+            g if g < 0. => self.shrink(tool(-g)),
+            _ => self,
+        }
+    }
+
     /// Create a spheroid with radii, `r1, r2, r3` centered at the origin.
     pub fn spheroid(radii: XYZ) -> D3 {
-        D3::Sphere(X(1.0)).scale3(radii)
+        D3::Sphere(X(1.0, None)).scale3(radii)
     }
 
     pub fn color(self, color_name: ColorEnum) -> D3 {
@@ -177,7 +196,7 @@ impl D3 {
 
     /// Scale size by the factor `s`.
     pub fn scale<T: Into<X>>(self, scale_factor: T) -> D3 {
-        D3::Scale(scale_factor.into(), Box::new(self.clone()))
+        D3::Scale(scale_factor.into(), Box::new(self))
     }
 
     /// Scale in `x` and `y` directions.
@@ -186,21 +205,25 @@ impl D3 {
     }
 
     pub fn translate(self, xyz: XYZ) -> D3 {
-        D3::Translate(xyz, Box::new(self))
+        match self { // Combine translations if possible
+            D3::Translate(v, shape) => D3::Translate(v + xyz, shape),
+            _ => D3::Translate(xyz, Box::new(self)),
+        }
     }
 
 
-    pub fn iter_translate<'a>(&'a self, xyz: XYZ, n: u32) -> impl Iterator<Item = D3> + 'a {
-        (0..n).map(move |ii| self.clone().translate(v3(xyz.0 * ii as f32, xyz.1 * ii as f32, xyz.2 * ii as f32)))
-    }
-
     // pub fn iter_translate2<'a, X: Into<X> + 'a, Y: Into<X> + 'a, Z: Into<X> + 'a>(&'a self, x: X, y: Y, z: Z, n: u32) 
         // -> impl Iterator<Item = D3> + 'a {
         // (0..n).map(move |ii| self.clone().translate(x.clone().into(), y.clone().into(), z.clone().into()))
     // }
 
     pub fn rotate(self, xyz: XYZ) -> D3 {
-        D3::Rotate(xyz, Box::new(self.clone()))
+        D3::Rotate(xyz, Box::new(self))
+    }
+
+    /// Reflect through the plane through the origin with normal `xyz`.
+    pub fn mirror(self, xyz: XYZ) -> D3 {
+        D3::Mirror(xyz, Box::new(self))
     }
 
 
@@ -210,14 +233,7 @@ impl D3 {
     }
 
     pub fn add(self, other: D3) -> D3 {
-        match self { // Combine Unions if possible
-            D3::Union(vec) => {
-                let mut vec = vec;
-                vec.push(other);
-                D3::Union(vec)
-                },
-            _ => D3::Union(Box::new(vec![self, other])),
-        }
+        self.combine(BoolOp::Union, other)
     }
 
     pub fn difference(self, other: D3) -> D3 {
@@ -225,85 +241,42 @@ impl D3 {
     }
 
     pub fn minkowski(self, other: D3) -> D3 {
-        D3::Minkowski(Box::new(vec![self, other]))
-        /*
-        match self { // Combine Minkowski sums if possible
-            D2::Join("minkowski", vec) => {
-                let mut vec = vec;
-                vec.push(other);
-                D2::Join("minkowski", vec)
-                },
-            _ => D2::Join("minkowski", Box::new(vec![self, other])),
-        }
-        */
-    }
-
-    pub fn add_map<F>(self, f: F) -> D3 where F: Fn(D3) -> D3 {
-        self.clone().add(f(self))
-    }
-
-    pub fn map<F>(self, f: F) -> D3 where F: Fn(D3) -> D3 {
-        f(self)
-    }
-
-
-    pub fn iter_rotate<'a>(&'a self, theta: XYZ, n: u32) -> impl Iterator<Item = D3> + 'a {
-        (0..n).map(move |ii| self.clone().rotate(v3(theta.0 * ii as f32, theta.1 * ii as f32, theta.2 * ii as f32)))
+        self.combine(BoolOp::Minkowski, other)
     }
 
     pub fn hull(self) -> D3 {
-        // D3::Hull(Box::new(vec![self]))
-        match self { // Combine Unions if possible
-            D3::Union(vec) => D3::Hull(vec),
-            _ => D3::Hull(Box::new(vec![self])),
-        }
-    // pub fn hull(self, other: D3) -> D3 {
-        // match self { // Combine D3 hulls if possible
-            // D3::Hull(vec) => {
-                // let mut vec = vec;
-                // vec.push(other);
-                // D3::Hull(vec)
-                // },
-            // _ => D3::Hull(Box::new(vec![self, other])),
-        // }
+        self.hull_of()
     }
 
     pub fn intersection(self, other: D3) -> D3 {
-        match self { // Combine intersections if possible
-            D3::Intersection(vec) => {
-                let mut vec = vec;
-                vec.push(other);
-                D3::Intersection(vec)
-                },
-            _ => D3::Intersection(Box::new(vec![self, other])),
-        }
+        self.combine(BoolOp::Intersection, other)
     }
 
     pub fn beveled_box<T: Into<X>>(xyz: XYZ, bevel_in: T) -> D3 {
         let x = xyz.0; 
         let y = xyz.1;
         let z = xyz.2;
-        let bevel = bevel_in.into();
-        D3::Hull(Box::new(vec![
+        let bevel = bevel_in.into().0;
+        D3::join(BoolOp::Hull, vec![
             D3::cuboid(v3(x,y-bevel*2.,z-bevel*2.)).translate(v3(0.,bevel,bevel)),
             D3::cuboid(v3(x-bevel*2.,y-bevel*2.,z)).translate(v3(bevel,bevel,0.)),
             D3::cuboid(v3(x-bevel*2.,y,z-bevel*2.)).translate(v3(bevel,0.,bevel)),
-            ]))
+            ])
     }
 
     pub fn beveled_cube_block<T0: Into<X>, T1: Into<X>, T2: Into<X>>(xyz_dim: (u32, u32, u32), i_cube_side: T0, i_bevel: T1, i_gap: T2) -> D3 {
-        let cube_side: X = i_cube_side.into(); 
-        let bevel: X = i_bevel.into(); 
-        let gap: X = i_gap.into(); 
-        D3::beveled_box(v3(cube_side-2*gap, cube_side-2*gap, cube_side-2*gap), bevel)
+        let cube_side = i_cube_side.into().0;
+        let bevel = i_bevel.into().0;
+        let gap = i_gap.into().0;
+        D3::beveled_box(v3(cube_side-2.*gap, cube_side-2.*gap, cube_side-2.*gap), bevel)
             .translate(v3(gap, gap, gap))
-            .iter_translate(v3(cube_side.0, 0., 0.), xyz_dim.0).union()
-            .iter_translate(v3(0, cube_side.0, 0.), xyz_dim.1).union()
-            .iter_translate(v3(0, 0, cube_side.0), xyz_dim.2).union()
+            .iter_translate(v3(cube_side, 0., 0.), xyz_dim.0).union()
+            .iter_translate(v3(0, cube_side, 0.), xyz_dim.1).union()
+            .iter_translate(v3(0, 0, cube_side), xyz_dim.2).union()
             .add(D3::cuboid(v3(
-                        cube_side*xyz_dim.0 - 2*(gap + bevel),
-                        cube_side*xyz_dim.1 - 2*(gap + bevel),
-                        cube_side*xyz_dim.2 - 2*(gap + bevel)
+                        cube_side*xyz_dim.0 as f32 - 2.*(gap + bevel),
+                        cube_side*xyz_dim.1 as f32 - 2.*(gap + bevel),
+                        cube_side*xyz_dim.2 as f32 - 2.*(gap + bevel)
                         )).translate(v3(gap+bevel,gap+bevel,gap+bevel))
                     )
     }
@@ -313,15 +286,15 @@ impl D3 {
         ///    1) Centered at the origin
         ///    2) Angle of attack is 30 degrees for the transition from cube face to sphere.
         let side: X = i_side.into();
-        D3::cube(side)
-            .translate(v3(-side*0.5,-side*0.5,-side*0.5))
+        D3::cube(side.clone())
+            .center()
             .intersection(D3::sphere(side * (1.0/3.0_f32.sqrt())))
     }
 
     pub fn truncated_octahedron(l_edge: f64) -> D3 {
         //* Create a truncated ocatahedron with edge length `l_edge` centered at the origin
         let r_square = 2.0_f64.powf(0.5) * l_edge;  // height of truncated octahedron between square faces
-        D3::Hull(Box::new(vec![
+        D3::join(BoolOp::Hull, vec![
             D3::cuboid(v3(l_edge, l_edge, 2.0*r_square))
                 .translate(v3(-l_edge/2.0, -l_edge/2.0, -r_square))
                 .rotate(v3(0., 0., 45.)),
@@ -331,7 +304,7 @@ impl D3 {
             D3::cuboid(v3(2.*r_square, l_edge, l_edge))
                 .translate(v3(-r_square, -l_edge/2.0, -l_edge/2.0))
                 .rotate(v3(45, 0, 0)),
-            ]))
+            ])
     }
 
     /*
@@ -342,7 +315,7 @@ impl D3 {
         // let r_square = 2.0_f64.powf(0.5) * l_edge;  // height of truncated octahedron between square faces
         let r_square = r; // r.into() TODO
         let l_edge = 2.0_f64.powf(0.5) / r_square;  // height of truncated octahedron between square faces
-        D3::Hull(Box::new(vec![
+        D3::join(BoolOp::Hull, vec![
             D3::cuboid(v3(l_edge, l_edge, 2.0*r_square))
                 .translate(v3(-l_edge/2.0, -l_edge/2.0, -r_square))
                 .rotate(v3(0., 0., 45.)),
@@ -352,7 +325,7 @@ impl D3 {
             D3::cuboid(v3(2.*r_square, l_edge, l_edge))
                 .translate(v3(-r_square, -l_edge/2.0, -l_edge/2.0))
                 .rotate(v3(45, 0, 0)),
-            ]))
+            ])
     }
     */
 
@@ -409,6 +382,21 @@ mod test {
         "color(\"red\") {\n  union() {\n    sphere(r = 7);\n    cube(size = 9);\n  }\n}"
         );
     }
+
+    #[test]
+    fn test_offset() {
+        assert_eq!(D3::cube(9).offset(0.5).scad(),
+        "minkowski() {\n  cube(size = 9);\n  sphere(r = 0.5);\n}"
+        );
+        assert_eq!(D3::cube(9).offset(0).scad(), "cube(size = 9);");
+    }
+
+    #[test]
+    fn test_offset_inward() {
+        assert_eq!(D3::sphere(1).offset(-0.25).scad(),
+        "difference() {\n  translate(v = [-2, -2, -2]) {\n    cube(size = [4, 4, 4]);\n  }\n  minkowski() {\n    difference() {\n      translate(v = [-2.5, -2.5, -2.5]) {\n        cube(size = [5, 5, 5]);\n      }\n      sphere(r = 1);\n    }\n    sphere(r = 0.25);\n  }\n}"
+        );
+    }
 }
     #[test]
     fn test_iter_translate() {
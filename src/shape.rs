@@ -0,0 +1,153 @@
+//! Operations shared by `D2` and `D3`, so pattern helpers are written once for both.
+
+use crate::*;
+
+/// A shape that can be moved, copied and combined. `D2` and `D3` implement it with their own vector and angle types.
+pub trait Shape: Csg {
+    /// Displacement, `XY` or `XYZ`.
+    type Vector: Copy + std::ops::Mul<f32, Output = Self::Vector>;
+    /// Rotation in degrees, `X` about Z for `D2` and `XYZ` about X, Y then Z for `D3`.
+    type Angle: Clone + std::ops::Mul<f32, Output = Self::Angle>;
+
+    /// Rotation of `degrees` about the Z axis.
+    fn about_z(degrees: f32) -> Self::Angle;
+
+    fn translate(self, v: Self::Vector) -> Self;
+
+    fn rotate(self, angle: Self::Angle) -> Self;
+
+    /// Reflect through the plane, or line, through the origin with the given normal.
+    fn mirror(self, normal: Self::Vector) -> Self;
+
+    fn scale(self, s: X) -> Self;
+
+    fn color(self, color: ColorEnum) -> Self;
+
+    fn difference(self, other: Self) -> Self;
+
+    /// Union of `self` and `f(self)`.
+    fn add_map<F: Fn(Self) -> Self>(self, f: F) -> Self {
+        self.clone().combine(BoolOp::Union, f(self))
+    }
+
+    fn map<F: Fn(Self) -> Self>(self, f: F) -> Self {
+        f(self)
+    }
+
+    /// `n` copies where copy `i` is translated by `i * step`.
+    fn iter_translate(&self, step: Self::Vector, n: u32) -> impl Iterator<Item = Self> + '_ {
+        (0..n).map(move |ii| self.clone().translate(step * ii as f32))
+    }
+
+    /// `n` copies where copy `i` is rotated by `i * angle`.
+    fn iter_rotate(&self, angle: Self::Angle, n: u32) -> impl Iterator<Item = Self> + '_ {
+        (0..n).map(move |ii| self.clone().rotate(angle.clone() * ii as f32))
+    }
+
+    /// Union of `n` copies spaced by `step`.
+    fn array(self, step: Self::Vector, n: u32) -> Self {
+        self.iter_translate(step, n).union()
+    }
+
+    /// Union of an `na` by `nb` grid of copies spaced by `a` and `b`.
+    fn grid(self, a: Self::Vector, na: u32, b: Self::Vector, nb: u32) -> Self {
+        self.array(a, na).array(b, nb)
+    }
+
+    /// Union of `self` and its mirror image.
+    fn mirrored(self, normal: Self::Vector) -> Self {
+        self.add_map(|s| s.mirror(normal))
+    }
+
+    /// Union of `n` copies spaced evenly around the Z axis.
+    fn polar(self, n: u32) -> Self {
+        self.iter_rotate(Self::about_z(360. / n as f32), n).union()
+    }
+}
+
+impl Shape for D2 {
+    type Vector = XY;
+    type Angle = X;
+
+    fn about_z(degrees: f32) -> X {
+        X::from(degrees)
+    }
+
+    fn translate(self, v: XY) -> D2 {
+        D2::translate(self, v)
+    }
+
+    fn rotate(self, angle: X) -> D2 {
+        D2::rotate(self, angle)
+    }
+
+    fn mirror(self, normal: XY) -> D2 {
+        D2::mirror(self, normal)
+    }
+
+    fn scale(self, s: X) -> D2 {
+        D2::scale(self, s)
+    }
+
+    fn color(self, color: ColorEnum) -> D2 {
+        D2::color(self, color)
+    }
+
+    fn difference(self, other: D2) -> D2 {
+        D2::difference(self, other)
+    }
+}
+
+impl Shape for D3 {
+    type Vector = XYZ;
+    type Angle = XYZ;
+
+    fn about_z(degrees: f32) -> XYZ {
+        v3(0, 0, degrees)
+    }
+
+    fn translate(self, v: XYZ) -> D3 {
+        D3::translate(self, v)
+    }
+
+    fn rotate(self, angle: XYZ) -> D3 {
+        D3::rotate(self, angle)
+    }
+
+    fn mirror(self, normal: XYZ) -> D3 {
+        D3::mirror(self, normal)
+    }
+
+    fn scale(self, s: X) -> D3 {
+        D3::scale(self, s)
+    }
+
+    fn color(self, color: ColorEnum) -> D3 {
+        D3::color(self, color)
+    }
+
+    fn difference(self, other: D3) -> D3 {
+        D3::difference(self, other)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ring<S: Shape>(shape: S, offset: S::Vector, n: u32) -> S {
+        shape.translate(offset).polar(n)
+    }
+
+    #[test]
+    fn test_generic_helpers() {
+        assert_eq!(D2::square(1).array(v2(2, 0), 2).scad(),
+            "union() {\n  translate(v = [0, 0]) {\n    square(size = 1);\n  }\n  translate(v = [2, 0]) {\n    square(size = 1);\n  }\n}");
+        assert_eq!(D3::cube(1).mirrored(v3(1, 0, 0)).bounds(), BBox3::new(v3(-1, 0, 0), v3(1, 1, 1)));
+        assert_eq!(D2::circle(1).polar(3).scad(),
+            "union() {\n  rotate(0) {\n    circle(d = 1);\n  }\n  rotate(120) {\n    circle(d = 1);\n  }\n  rotate(240) {\n    circle(d = 1);\n  }\n}");
+        assert_eq!(D3::cube(1).iter_rotate(v3(0, 0, 90), 2).count(), 2);
+        assert_eq!(ring(D2::circle(2), v2(5, 0), 4).bounds(), BBox2::new(v2(-6, -6), v2(6, 6)));
+        assert_eq!(ring(D3::sphere(1), v3(5, 0, 0), 4).as_join().map(|(_, v)| v.len()), Some(4));
+    }
+}
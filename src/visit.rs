@@ -0,0 +1,204 @@
+//! Walking, searching and rewriting shape trees, including the `D2` shapes under extrusions.
+
+use crate::*;
+
+/// A node of a shape tree.
+#[derive(Clone, Copy, Debug)]
+pub enum Node<'a> {
+    D2(&'a D2),
+    D3(&'a D3),
+}
+
+impl<'a> Node<'a> {
+    pub fn as_d2(&self) -> Option<&'a D2> {
+        match self {
+            Node::D2(shape) => Some(shape),
+            Node::D3(_) => None,
+        }
+    }
+
+    pub fn as_d3(&self) -> Option<&'a D3> {
+        match self {
+            Node::D3(shape) => Some(shape),
+            Node::D2(_) => None,
+        }
+    }
+
+    /// The direct children of the node.
+    pub fn children(&self) -> Vec<Node<'a>> {
+        match self {
+            Node::D2(shape) => shape.children().into_iter().map(Node::D2).collect(),
+            Node::D3(shape) => match shape {
+                D3::LinearExtrude(_, d2) | D3::RotateExtrude(_, d2) => vec![Node::D2(d2)],
+                _ => shape.children().into_iter().map(Node::D3).collect(),
+            },
+        }
+    }
+
+    /// Visit the node and then its descendants, depth first.
+    pub fn walk<F: FnMut(Node<'a>)>(self, f: &mut F) {
+        f(self);
+        for child in self.children() {
+            child.walk(f);
+        }
+    }
+}
+
+impl D3 {
+    /// The direct `D3` children. Shapes under extrusions are `D2`, see `Node::children`.
+    pub fn children(&self) -> Vec<&D3> {
+        match self {
+            D3::Color(_, shape) | D3::Translate(_, shape) | D3::Scale(_, shape) | D3::Scale3(_, shape)
+                | D3::Rotate(_, shape) | D3::Mirror(_, shape) | D3::Animate(_, shape) | D3::Named(_, shape) => vec![shape],
+            D3::Difference(a, b) => vec![a, b],
+            D3::Join(_, v) => v.iter().collect(),
+            D3::Cube(_) | D3::Cuboid(_) | D3::Cylinder(_, _) | D3::Sphere(_) | D3::Polyhedron(_, _)
+                | D3::LinearExtrude(_, _) | D3::RotateExtrude(_, _) => vec![],
+        }
+    }
+
+    /// Replace the direct children by `f3` of them, and the `D2` under an extrusion by `f2` of it.
+    pub fn map_children<F3: FnMut(D3) -> D3, F2: FnMut(D2) -> D2>(self, f3: &mut F3, f2: &mut F2) -> D3 {
+        let mut map = |shape: Box<D3>| Box::new(f3(*shape));
+        match self {
+            D3::Color(c, shape) => D3::Color(c, map(shape)),
+            D3::Translate(xyz, shape) => D3::Translate(xyz, map(shape)),
+            D3::Scale(s, shape) => D3::Scale(s, map(shape)),
+            D3::Scale3(xyz, shape) => D3::Scale3(xyz, map(shape)),
+            D3::Rotate(xyz, shape) => D3::Rotate(xyz, map(shape)),
+            D3::Mirror(xyz, shape) => D3::Mirror(xyz, map(shape)),
+            D3::Animate(keys, shape) => D3::Animate(keys, map(shape)),
+            D3::Named(meta, shape) => D3::Named(meta, map(shape)),
+            D3::Difference(a, b) => D3::Difference(map(a), map(b)),
+            D3::Join(op, v) => D3::Join(op, Box::new(v.into_iter().map(|x| *map(Box::new(x))).collect())),
+            D3::LinearExtrude(h, d2) => D3::LinearExtrude(h, Box::new(f2(*d2))),
+            D3::RotateExtrude(a, d2) => D3::RotateExtrude(a, Box::new(f2(*d2))),
+            shape => shape,
+        }
+    }
+
+    /// Visit every node, `D3` and `D2`, depth first starting with `self`.
+    pub fn walk<'a, F: FnMut(Node<'a>)>(&'a self, mut f: F) {
+        Node::D3(self).walk(&mut f);
+    }
+
+    /// Combine `f` over every node depth first, starting from `init`.
+    pub fn fold<'a, T, F: FnMut(T, Node<'a>) -> T>(&'a self, init: T, mut f: F) -> T {
+        let mut acc = Some(init);
+        self.walk(|node| acc = Some(f(acc.take().unwrap(), node)));
+        acc.unwrap()
+    }
+
+    /// The first node depth first for which `pred` holds.
+    pub fn find<'a, F: Fn(Node<'a>) -> bool>(&'a self, pred: F) -> Option<Node<'a>> {
+        self.fold(None, |found, node| found.or_else(|| Some(node).filter(|n| pred(*n))))
+    }
+
+    /// Rebuild the tree bottom up, replacing every `D3` node by `f` of it after its children.
+    pub fn map_nodes<F: FnMut(D3) -> D3>(self, f: &mut F) -> D3 {
+        let shape = self.map_children(&mut |child: D3| child.map_nodes(f), &mut |d2| d2);
+        f(shape)
+    }
+
+    /// Rebuild every `D2` under an extrusion bottom up with `f`, see `D2::map_nodes`.
+    pub fn map_d2_nodes<F: FnMut(D2) -> D2>(self, f: &mut F) -> D3 {
+        match self {
+            D3::LinearExtrude(h, d2) => D3::LinearExtrude(h, Box::new(d2.map_nodes(f))),
+            D3::RotateExtrude(angle, d2) => D3::RotateExtrude(angle, Box::new(d2.map_nodes(f))),
+            shape => shape.map_children(&mut |child: D3| child.map_d2_nodes(f), &mut |d2| d2),
+        }
+    }
+}
+
+impl D2 {
+    /// The direct children.
+    pub fn children(&self) -> Vec<&D2> {
+        match self {
+            D2::Color(_, shape) | D2::Rotate(_, shape) | D2::Scale(_, shape) | D2::Scale2(_, shape)
+                | D2::Translate(_, shape) | D2::Mirror(_, shape) => vec![shape],
+            D2::Difference(a, b) => vec![a, b],
+            D2::Join(_, v) => v.iter().collect(),
+            D2::Circle(_) | D2::Square(_) | D2::Rectangle(_) | D2::Polygon(_) => vec![],
+        }
+    }
+
+    /// Replace the direct children by `f` of them.
+    pub fn map_children<F: FnMut(D2) -> D2>(self, f: &mut F) -> D2 {
+        let mut map = |shape: Box<D2>| Box::new(f(*shape));
+        match self {
+            D2::Color(c, shape) => D2::Color(c, map(shape)),
+            D2::Rotate(theta, shape) => D2::Rotate(theta, map(shape)),
+            D2::Scale(s, shape) => D2::Scale(s, map(shape)),
+            D2::Scale2(xy, shape) => D2::Scale2(xy, map(shape)),
+            D2::Translate(xy, shape) => D2::Translate(xy, map(shape)),
+            D2::Mirror(xy, shape) => D2::Mirror(xy, map(shape)),
+            D2::Difference(a, b) => D2::Difference(map(a), map(b)),
+            D2::Join(op, v) => D2::Join(op, Box::new(v.into_iter().map(|x| *map(Box::new(x))).collect())),
+            shape => shape,
+        }
+    }
+
+    /// Visit every node depth first starting with `self`.
+    pub fn walk<'a, F: FnMut(Node<'a>)>(&'a self, mut f: F) {
+        Node::D2(self).walk(&mut f);
+    }
+
+    /// Combine `f` over every node depth first, starting from `init`.
+    pub fn fold<'a, T, F: FnMut(T, Node<'a>) -> T>(&'a self, init: T, mut f: F) -> T {
+        let mut acc = Some(init);
+        self.walk(|node| acc = Some(f(acc.take().unwrap(), node)));
+        acc.unwrap()
+    }
+
+    /// The first node depth first for which `pred` holds.
+    pub fn find<'a, F: Fn(Node<'a>) -> bool>(&'a self, pred: F) -> Option<Node<'a>> {
+        self.fold(None, |found, node| found.or_else(|| Some(node).filter(|n| pred(*n))))
+    }
+
+    /// Rebuild the tree bottom up, replacing every node by `f` of it after its children.
+    pub fn map_nodes<F: FnMut(D2) -> D2>(self, f: &mut F) -> D2 {
+        let shape = self.map_children(&mut |child: D2| child.map_nodes(f));
+        f(shape)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn model() -> D3 {
+        D3::sphere(2)
+            .add(D3::cube(1).translate(v3(3, 0, 0)))
+            .add(vec![D3::sphere(1), D3::cube(2)].into_iter().hull())
+            .add(D2::circle(4).add(D2::square(1)).linear_extrude(2))
+    }
+
+    #[test]
+    fn test_fold_find() {
+        let model = model();
+        let hulls = model.fold(0, |n, node| n + matches!(node, Node::D3(D3::Join(BoolOp::Hull, _))) as u32);
+        assert_eq!(hulls, 1);
+        assert_eq!(model.fold(0, |n, _| n + 1), 11);
+        assert!(matches!(model.find(|node| matches!(node, Node::D2(D2::Square(_)))), Some(Node::D2(D2::Square(_)))));
+        assert!(model.find(|node| matches!(node.as_d3(), Some(D3::Cylinder(_, _)))).is_none());
+    }
+
+    #[test]
+    fn test_map_nodes() {
+        let recolored = model().map_nodes(&mut |shape| match shape {
+            D3::Sphere(_) => shape.color(ColorEnum::Red),
+            shape => shape,
+        });
+        assert_eq!(recolored.fold(0, |n, node| n + matches!(node, Node::D3(D3::Color(_, _))) as u32), 2);
+        let beveled = model().map_nodes(&mut |shape| match shape {
+            D3::Cube(X(s, _)) => D3::beveled_box(v3(s, s, s), s / 10.),
+            shape => shape,
+        });
+        assert!(beveled.find(|node| matches!(node, Node::D3(D3::Cube(_)))).is_none());
+        let squares = model().map_d2_nodes(&mut |shape| match shape {
+            D2::Circle(d) => D2::square(d),
+            shape => shape,
+        });
+        assert!(squares.scad().contains("square(size = 4);"));
+    }
+}